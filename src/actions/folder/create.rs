@@ -4,7 +4,18 @@ use std::fs;
 use thiserror::Error;
 use tracing::debug;
 
-use crate::actions::folder::model::Folder;
+use crate::{
+    actions::folder::{
+        index::{IndexError, NoteIndex},
+        model::Folder,
+        sync::{
+            mutation::{Mutation, MutationAction},
+            sync_note::SyncError,
+        },
+    },
+    config::model::Config,
+    output,
+};
 
 #[derive(Error, Debug)]
 pub enum FolderCreationError {
@@ -13,10 +24,18 @@ pub enum FolderCreationError {
 
     #[error("failed to create folder: {0}")]
     Create(String),
+
+    #[error(transparent)]
+    Index(#[from] IndexError),
+
+    #[error(transparent)]
+    Sync(#[from] SyncError),
 }
 
 impl Folder {
-    pub fn create(&self) -> Result<(), FolderCreationError> {
+    /// Creates the folder and commits it. It starts out with an empty index, because git
+    /// doesn't track empty directories.
+    pub fn create(&self, config: &Config) -> Result<(), FolderCreationError> {
         let path = self.get_path();
 
         if path.exists() {
@@ -26,7 +45,30 @@ impl Folder {
         fs::create_dir(path.clone()).map_err(|e| FolderCreationError::Create(e.to_string()))?;
         debug!("Created directory '{}'", path.to_str().unwrap());
 
-        println!("Created folder '{}'", self.name.blue());
+        self.index_save(&NoteIndex::default())?;
+
+        output::message(&format!("Created folder '{}'", self.name.blue()));
+
+        let name = output::relative_path(&path, &config.data_dir);
+        Mutation::new(MutationAction::CreateFolder, name, vec![self.index_path()])
+            .commit(config)?;
+
+        Ok(())
+    }
+
+    /// Creates the folder together with any missing parent folders. Unlike [`Folder::create`],
+    /// it's fine if the folder already exists.
+    pub fn create_all(&self) -> Result<(), FolderCreationError> {
+        let path = self.get_path();
+
+        if path.is_dir() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(path.clone()).map_err(|e| FolderCreationError::Create(e.to_string()))?;
+        debug!("Created directories '{}'", path.to_str().unwrap());
+
+        output::message(&format!("Created folder '{}'", self.name.blue()));
         Ok(())
     }
 }
@@ -3,6 +3,7 @@ use thiserror::Error;
 use crate::actions::{
     folder::{list::ListFolderError, model::Folder},
     note::model::{Note, NoteError},
+    search_index::model::SearchIndex,
 };
 
 #[derive(Error, Debug)]
@@ -15,19 +16,26 @@ pub enum GetNotesByNameError {
 }
 
 impl Folder {
-    pub fn get_notes_by_name(&self, name: &str) -> Result<Vec<Note>, GetNotesByNameError> {
+    /// Returns all notes below this folder whose name contains `name` and that have all `tags`.
+    /// Titles, aliases and tags are looked up in the search index.
+    pub fn get_notes_by_name(
+        &self,
+        name: &str,
+        tags: &[String],
+        index: &SearchIndex,
+    ) -> Result<Vec<Note>, GetNotesByNameError> {
         let entries = self.list()?;
 
         let mut matching_notes = Vec::new();
 
         for note in entries.notes.into_iter() {
-            if note.get_name()?.to_lowercase().contains(name) {
+            if index.matches_name(&note, name)? && index.has_tags(&note, tags)? {
                 matching_notes.push(note);
             }
         }
 
         for folder in entries.folders.into_iter() {
-            matching_notes.append(&mut folder.get_notes_by_name(name)?);
+            matching_notes.append(&mut folder.get_notes_by_name(name, tags, index)?);
         }
 
         Ok(matching_notes)
@@ -0,0 +1,296 @@
+use std::{collections::HashSet, fs, path::PathBuf};
+
+use thiserror::Error;
+use tracing::debug;
+
+use crate::actions::folder::model::Folder;
+
+#[derive(Error, Debug)]
+pub enum IndexError {
+    #[error("failed to access the note index: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+type Error = IndexError;
+
+/// The name of the per-folder file that keeps track of note ids.
+pub const INDEX_FILE_NAME: &str = ".index";
+
+/// Maps note file names to stable numeric ids, the same way nb does it.
+/// Line `n` of the index file holds the note with the id `n`, deleted notes leave an empty line
+/// behind so that the ids of the following notes don't shift.
+#[derive(Default, Debug)]
+pub struct NoteIndex {
+    entries: Vec<Option<String>>,
+}
+
+impl NoteIndex {
+    pub fn parse(content: &str) -> Self {
+        let entries = content
+            .lines()
+            .map(|line| match line.trim() {
+                "" => None,
+                value => Some(value.to_string()),
+            })
+            .collect();
+
+        Self { entries }
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut content = String::new();
+        for entry in self.entries.iter() {
+            content.push_str(entry.as_deref().unwrap_or(""));
+            content.push('\n');
+        }
+
+        content
+    }
+
+    pub fn id_of(&self, name: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.as_deref() == Some(name))
+            .map(|index| index + 1)
+    }
+
+    pub fn name_of(&self, id: usize) -> Option<&str> {
+        self.entries
+            .get(id.checked_sub(1)?)
+            .and_then(|entry| entry.as_deref())
+    }
+
+    /// Adds a note and returns its id. Returns the existing id if the note is already indexed.
+    pub fn add(&mut self, name: &str) -> usize {
+        if let Some(id) = self.id_of(name) {
+            return id;
+        }
+
+        self.entries.push(Some(name.to_string()));
+        self.entries.len()
+    }
+
+    /// Adds a note back with the id it had before. If another note took that id in the meantime,
+    /// it gets a new one like [`NoteIndex::add`].
+    pub fn restore(&mut self, name: &str, id: usize) -> usize {
+        if let Some(existing) = self.id_of(name) {
+            return existing;
+        }
+
+        if id == 0 || self.name_of(id).is_some() {
+            return self.add(name);
+        }
+
+        if self.entries.len() < id {
+            self.entries.resize(id, None);
+        }
+        self.entries[id - 1] = Some(name.to_string());
+
+        id
+    }
+
+    /// Removes a note, keeping its line so that no other id changes.
+    pub fn remove(&mut self, name: &str) -> bool {
+        match self.id_of(name) {
+            None => false,
+            Some(id) => {
+                self.entries[id - 1] = None;
+                true
+            }
+        }
+    }
+
+    fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().filter_map(|entry| entry.as_deref())
+    }
+
+    /// Merges the changes `theirs` made since `ancestor` into this index. Notes they added get
+    /// the next free ids here, notes they removed are removed here as well. A note they renamed
+    /// shows up as a new name at the id of the old one and keeps its id here.
+    pub fn merge(&mut self, ancestor: &NoteIndex, theirs: &NoteIndex) {
+        for name in ancestor.names() {
+            if theirs.id_of(name).is_some() {
+                continue;
+            }
+
+            let renamed = ancestor
+                .id_of(name)
+                .and_then(|id| theirs.name_of(id))
+                .filter(|new| ancestor.id_of(new).is_none() && self.id_of(new).is_none());
+            match renamed {
+                Some(new) if self.rename(name, new).is_some() => {}
+                _ => {
+                    self.remove(name);
+                }
+            }
+        }
+
+        for name in theirs.names() {
+            if ancestor.id_of(name).is_none() {
+                self.add(name);
+            }
+        }
+    }
+
+    /// Renames a note in place so that it keeps its id.
+    pub fn rename(&mut self, old: &str, new: &str) -> Option<usize> {
+        let id = self.id_of(old)?;
+        self.entries[id - 1] = Some(new.to_string());
+        Some(id)
+    }
+}
+
+impl Folder {
+    pub fn index_path(&self) -> PathBuf {
+        let mut path = self.get_path();
+        path.push(INDEX_FILE_NAME);
+        path
+    }
+
+    pub fn index_load(&self) -> Result<NoteIndex, Error> {
+        let path = self.index_path();
+        if !path.exists() {
+            return Ok(NoteIndex::default());
+        }
+
+        Ok(NoteIndex::parse(&fs::read_to_string(path)?))
+    }
+
+    pub fn index_save(&self, index: &NoteIndex) -> Result<(), Error> {
+        fs::write(self.index_path(), index.serialize())?;
+        Ok(())
+    }
+
+    /// Brings the index in line with the files on disk: notes that vanished are dropped and new
+    /// notes are appended in name order. Only writes the index if something changed.
+    pub fn index_reconcile(&self, note_names: &[String]) -> Result<NoteIndex, Error> {
+        let mut index = self.index_load()?;
+        let mut changed = false;
+
+        let existing: HashSet<&str> = note_names.iter().map(|name| name.as_str()).collect();
+
+        for entry in index.entries.iter_mut() {
+            if entry
+                .as_deref()
+                .is_some_and(|name| !existing.contains(name))
+            {
+                debug!("Dropping {:?} from the index", entry);
+                *entry = None;
+                changed = true;
+            }
+        }
+
+        let mut new_names: Vec<&String> = note_names
+            .iter()
+            .filter(|name| index.id_of(name).is_none())
+            .collect();
+        new_names.sort();
+
+        for name in new_names {
+            index.add(name);
+            changed = true;
+        }
+
+        if changed {
+            self.index_save(&index)?;
+        }
+
+        Ok(index)
+    }
+
+    /// Reconciles the index with the notes currently in the folder.
+    pub fn index_refresh(&self) -> Result<NoteIndex, Error> {
+        let mut note_names = Vec::new();
+
+        for entry in fs::read_dir(self.get_path())? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            if entry.file_type()?.is_file() && !name.starts_with(".") {
+                note_names.push(name);
+            }
+        }
+
+        self.index_reconcile(&note_names)
+    }
+
+    pub fn index_add(&self, name: &str) -> Result<usize, Error> {
+        let mut index = self.index_load()?;
+        let id = index.add(name);
+        self.index_save(&index)?;
+        Ok(id)
+    }
+
+    /// Adds a restored note to the index, with its old id if that is still free.
+    pub fn index_restore(&self, name: &str, id: Option<usize>) -> Result<usize, Error> {
+        let mut index = self.index_load()?;
+        let id = match id {
+            Some(id) => index.restore(name, id),
+            None => index.add(name),
+        };
+        self.index_save(&index)?;
+        Ok(id)
+    }
+
+    pub fn index_remove(&self, name: &str) -> Result<(), Error> {
+        let mut index = self.index_load()?;
+        if index.remove(name) {
+            self.index_save(&index)?;
+        }
+        Ok(())
+    }
+
+    pub fn index_rename(&self, old: &str, new: &str) -> Result<Option<usize>, Error> {
+        let mut index = self.index_load()?;
+        let id = index.rename(old, new);
+        if id.is_some() {
+            self.index_save(&index)?;
+        }
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_reuses_the_old_id() {
+        let mut index = NoteIndex::parse("a.md\nb.md\nc.md\n");
+        index.remove("a.md");
+
+        assert_eq!(index.restore("a.md", 1), 1);
+        assert_eq!(index.serialize(), "a.md\nb.md\nc.md\n");
+    }
+
+    #[test]
+    fn restore_gets_a_new_id_if_the_old_one_is_taken() {
+        let mut index = NoteIndex::parse("\nb.md\n");
+        index.rename("b.md", "d.md");
+        index.restore("b.md", 2);
+
+        assert_eq!(index.id_of("b.md"), Some(3));
+        assert_eq!(index.id_of("d.md"), Some(2));
+    }
+
+    #[test]
+    fn merge_keeps_the_id_of_a_note_renamed_on_their_side() {
+        let ancestor = NoteIndex::parse("a.md\nb.md\n");
+        let theirs = NoteIndex::parse("a.md\nc.md\n");
+        let mut ours = NoteIndex::parse("a.md\nb.md\nd.md\n");
+        ours.merge(&ancestor, &theirs);
+
+        assert_eq!(ours.serialize(), "a.md\nc.md\nd.md\n");
+    }
+
+    #[test]
+    fn merge_gives_a_new_id_to_a_note_added_in_place_of_a_removed_one() {
+        let ancestor = NoteIndex::parse("a.md\nb.md\n");
+        let theirs = NoteIndex::parse("a.md\nc.md\n");
+        let mut ours = NoteIndex::parse("a.md\n\nc.md\n");
+        ours.merge(&ancestor, &theirs);
+
+        assert_eq!(ours.id_of("b.md"), None);
+        assert_eq!(ours.id_of("c.md"), Some(3));
+    }
+}
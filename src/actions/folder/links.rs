@@ -0,0 +1,286 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use thiserror::Error;
+use tracing::debug;
+
+use crate::actions::folder::{list::ListFolderError, model::Folder};
+
+#[derive(Error, Debug)]
+pub enum RewriteLinksError {
+    #[error(transparent)]
+    ListFolder(#[from] ListFolderError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Resolves `.` and `..` components without touching the file system.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+
+    normalized
+}
+
+/// Returns `target` relative to the folder `base`. Both paths have to be normalized.
+pub fn relative_path(target: &Path, base: &Path) -> PathBuf {
+    let target: Vec<Component> = target.components().collect();
+    let base: Vec<Component> = base.components().collect();
+
+    let common = target
+        .iter()
+        .zip(base.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push("..");
+    }
+    for component in target[common..].iter() {
+        relative.push(component);
+    }
+
+    relative
+}
+
+/// Maps `path` to its new location if it is `from` or lies below it.
+fn moved_path(path: &Path, from: &Path, to: &Path) -> PathBuf {
+    match path.strip_prefix(from) {
+        Ok(rest) if rest.as_os_str().is_empty() => to.to_path_buf(),
+        Ok(rest) => to.join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Calls `rewrite` for the target of every inline markdown link (`[text](target)`) and replaces it
+/// with the result. Links inside fenced code blocks and code spans are examples, not links, and
+/// are left alone. Returns `None` if nothing changed.
+fn rewrite_link_targets(
+    content: &str,
+    mut rewrite: impl FnMut(&str) -> Option<String>,
+) -> Option<String> {
+    let mut result = String::with_capacity(content.len());
+    let mut changed = false;
+    let mut fence: Option<&str> = None;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+
+        if let Some(current) = fence {
+            if trimmed.starts_with(current) {
+                fence = None;
+            }
+            result.push_str(line);
+            continue;
+        }
+
+        if trimmed.starts_with("```") {
+            fence = Some("```");
+        } else if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+        }
+
+        if fence.is_some() {
+            result.push_str(line);
+            continue;
+        }
+
+        let mut rest = line;
+        while !rest.is_empty() {
+            let (text, code, after) = split_code_span(rest);
+            rewrite_text_links(text, &mut result, &mut changed, &mut rewrite);
+            result.push_str(code);
+            rest = after;
+        }
+    }
+
+    changed.then_some(result)
+}
+
+/// Splits `text` at its first code span into the text before it, the span including its
+/// backticks and the rest. A backtick without a closing one is plain text.
+fn split_code_span(text: &str) -> (&str, &str, &str) {
+    let start = match text.find('`') {
+        Some(value) => value,
+        None => return (text, "", ""),
+    };
+
+    let ticks = text[start..].chars().take_while(|c| *c == '`').count();
+    let delimiter = "`".repeat(ticks);
+    let inner = start + ticks;
+
+    match text[inner..].find(&delimiter) {
+        Some(end) => {
+            let end = inner + end + ticks;
+            (&text[..start], &text[start..end], &text[end..])
+        }
+        None => (&text[..inner], "", &text[inner..]),
+    }
+}
+
+/// Rewrites the link targets in a piece of text without code and appends it to `result`.
+fn rewrite_text_links(
+    text: &str,
+    result: &mut String,
+    changed: &mut bool,
+    rewrite: &mut impl FnMut(&str) -> Option<String>,
+) {
+    let mut rest = text;
+
+    while let Some(index) = rest.find("](") {
+        let (before, after) = rest.split_at(index + 2);
+        result.push_str(before);
+
+        let end = after.find([')', ' ', '\n']).unwrap_or(after.len());
+        let (target, after) = after.split_at(end);
+
+        match rewrite(target) {
+            Some(new_target) if new_target != target => {
+                result.push_str(&new_target);
+                *changed = true;
+            }
+            _ => result.push_str(target),
+        }
+
+        rest = after;
+    }
+
+    result.push_str(rest);
+}
+
+fn is_relative_link(target: &str) -> bool {
+    !(target.is_empty()
+        || target.contains("://")
+        || target.starts_with('#')
+        || target.starts_with('/')
+        || target.starts_with("mailto:"))
+}
+
+impl Folder {
+    /// Fixes relative markdown links in every note below this folder after `from` was moved to
+    /// `to`. This covers links pointing at the moved path as well as links inside moved notes.
+    /// Returns the paths of all notes that were changed.
+    pub fn rewrite_links(&self, from: &Path, to: &Path) -> Result<Vec<PathBuf>, RewriteLinksError> {
+        let entries = self.list()?;
+        let mut changed = Vec::new();
+
+        for note in entries.notes.iter() {
+            let path = note.get_path();
+
+            let is_markdown = path
+                .extension()
+                .is_some_and(|ext| ext == "md" || ext == "markdown");
+            if !is_markdown {
+                continue;
+            }
+
+            let new_path = normalize_path(&path);
+            // where the note was before the move, in case it was moved itself
+            let old_path = moved_path(&new_path, to, from);
+
+            let new_dir = new_path.parent().unwrap().to_path_buf();
+            let old_dir = old_path.parent().unwrap().to_path_buf();
+
+            let content = match fs::read_to_string(&path) {
+                Ok(value) => value,
+                Err(e) => {
+                    debug!("Not rewriting links in {:?}: {}", path, e);
+                    continue;
+                }
+            };
+
+            let new_content = rewrite_link_targets(&content, |target| {
+                if !is_relative_link(target) {
+                    return None;
+                }
+
+                let (link, fragment) = match target.split_once('#') {
+                    Some((link, fragment)) => (link, Some(fragment)),
+                    None => (target, None),
+                };
+
+                let old_target = normalize_path(&old_dir.join(link));
+                let new_target = moved_path(&old_target, from, to);
+
+                if old_target == new_target && old_dir == new_dir {
+                    return None;
+                }
+
+                let mut new_link = relative_path(&new_target, &new_dir).to_str()?.to_string();
+                if let Some(fragment) = fragment {
+                    new_link.push('#');
+                    new_link.push_str(fragment);
+                }
+
+                Some(new_link)
+            });
+
+            if let Some(new_content) = new_content {
+                debug!("Rewrote links in {:?}", path);
+                fs::write(&path, new_content)?;
+                changed.push(path);
+            }
+        }
+
+        for folder in entries.folders.iter() {
+            changed.append(&mut folder.rewrite_links(from, to)?);
+        }
+
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename(content: &str) -> Option<String> {
+        rewrite_link_targets(content, |target| {
+            (target == "old.md").then(|| "new.md".to_string())
+        })
+    }
+
+    #[test]
+    fn links_are_rewritten() {
+        assert_eq!(
+            rename("See [old](old.md) and [other](other.md).\n").as_deref(),
+            Some("See [old](new.md) and [other](other.md).\n")
+        );
+    }
+
+    #[test]
+    fn code_blocks_are_left_alone() {
+        let content = "```md\n[old](old.md)\n```\n~~~\n[old](old.md)\n~~~\n";
+
+        assert_eq!(rename(content), None);
+    }
+
+    #[test]
+    fn code_spans_are_left_alone() {
+        assert_eq!(
+            rename("Write `[old](old.md)` or ``[a](old.md)`` for [old](old.md)\n").as_deref(),
+            Some("Write `[old](old.md)` or ``[a](old.md)`` for [old](new.md)\n")
+        );
+    }
+
+    #[test]
+    fn unclosed_backtick_is_text() {
+        assert_eq!(
+            rename("a ` b [old](old.md)\n").as_deref(),
+            Some("a ` b [old](new.md)\n")
+        );
+    }
+}
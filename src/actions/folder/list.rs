@@ -1,13 +1,21 @@
-use std::fs;
+use std::{fs, path::Path};
 
 use colored::Colorize;
 use itertools::Itertools;
+use serde::Serialize;
 use thiserror::Error;
 use tracing::debug;
 
-use crate::actions::{
-    folder::model::{Folder, FolderError},
-    note::model::{Note, NoteError},
+use crate::{
+    actions::{
+        folder::{
+            index::IndexError,
+            model::{Folder, FolderError},
+        },
+        note::model::{Note, NoteError},
+        search_index::model::SearchIndex,
+    },
+    output,
 };
 
 #[derive(Error, Debug)]
@@ -20,6 +28,9 @@ pub enum ListFolderError {
 
     #[error(transparent)]
     Folder(#[from] FolderError),
+
+    #[error(transparent)]
+    Index(#[from] IndexError),
 }
 
 #[derive(Default)]
@@ -28,8 +39,86 @@ pub struct FolderSearchResult {
     pub notes: Vec<Note>,
 }
 
+/// A single entry of `ls` in the structured output formats. Paths are relative to the data
+/// directory.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ListEntry {
+    Folder {
+        path: String,
+    },
+    Note {
+        id: Option<usize>,
+        path: String,
+        title: Option<String>,
+        encrypted: bool,
+        size: u64,
+        modified: Option<String>,
+    },
+}
+
+impl ListEntry {
+    pub fn folder(folder: &Folder, root: &Path) -> Self {
+        ListEntry::Folder {
+            path: output::relative_path(&folder.get_path(), root),
+        }
+    }
+
+    pub fn note(note: &Note, root: &Path, index: &SearchIndex) -> Result<Self, ListFolderError> {
+        let metadata = fs::metadata(note.get_path())?;
+
+        Ok(ListEntry::Note {
+            id: note.id,
+            path: output::relative_path(&note.get_path(), root),
+            title: index.title_of(note)?,
+            encrypted: note.is_encrypted(),
+            size: metadata.len(),
+            modified: metadata.modified().ok().map(output::timestamp),
+        })
+    }
+}
+
+/// Formats a note as `[id] Title | name.md`, with the title taken from the search index.
+pub fn note_label(note: &Note, index: &SearchIndex) -> String {
+    let mut label = String::new();
+
+    if let Some(id) = note.id {
+        label.push_str(&format!("{} ", format!("[{}]", id).yellow()));
+    }
+
+    if let Some(pretty) = index.title_of(note).unwrap() {
+        label.push_str(&format!("{} | ", pretty.blue()));
+    }
+    label.push_str(&format!("{}", note.name.blue()));
+
+    if note.is_encrypted() {
+        label.push_str(&format!(" {}", "(locked)".red()));
+    }
+
+    label
+}
+
 impl FolderSearchResult {
-    pub fn print(&self) {
+    pub fn entries(
+        &self,
+        root: &Path,
+        index: &SearchIndex,
+    ) -> Result<Vec<ListEntry>, ListFolderError> {
+        let mut entries: Vec<ListEntry> = self
+            .folders
+            .iter()
+            .map(|folder| ListEntry::folder(folder, root))
+            .collect();
+
+        for note in self.notes.iter() {
+            entries.push(ListEntry::note(note, root, index)?);
+        }
+
+        Ok(entries)
+    }
+
+    /// Prints the entries. Note titles are taken from the search index.
+    pub fn print(&self, index: &SearchIndex) {
         let folders_string = self
             .folders
             .iter()
@@ -38,18 +127,7 @@ impl FolderSearchResult {
         let notes_string = self
             .notes
             .iter()
-            .map(|note| {
-                let name = {
-                    let mut name = String::new();
-                    if let Some(pretty) = note.get_pretty_name().unwrap() {
-                        name.push_str(&format!("{} | ", pretty.blue()));
-                    }
-                    name.push_str(&format!("{}", note.name.blue()));
-
-                    name
-                };
-                format!("- {}: {}", "F".green(), name)
-            })
+            .map(|note| format!("- {}: {}", "F".green(), note_label(note, index)))
             .join("\n");
 
         let entries = self.folders.len() + self.notes.len();
@@ -83,6 +161,10 @@ impl Folder {
 
                 folders.push(folder);
             } else if entry_type.is_file() {
+                if entry_name.to_str().unwrap().starts_with(".") {
+                    continue;
+                }
+
                 let note = Note::new(
                     self.get_path().to_str().unwrap(),
                     entry_name.to_str().unwrap(),
@@ -91,6 +173,15 @@ impl Folder {
             }
         }
 
+        let note_names: Vec<String> = notes.iter().map(|note| note.name.clone()).collect();
+        let index = self.index_reconcile(&note_names)?;
+
+        for note in notes.iter_mut() {
+            note.id = index.id_of(&note.name);
+        }
+        notes.sort_by_key(|note| note.id);
+        folders.sort_by(|a, b| a.name.cmp(&b.name));
+
         Ok(FolderSearchResult { folders, notes })
     }
 }
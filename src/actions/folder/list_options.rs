@@ -0,0 +1,281 @@
+use std::{cmp::Ordering, fs, path::Path, str::FromStr, time::SystemTime};
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone};
+
+use crate::actions::{
+    folder::{
+        list::{FolderSearchResult, ListFolderError},
+        model::Folder,
+    },
+    note::model::Note,
+    search_index::model::SearchIndex,
+};
+
+/// The order of `ls` entries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListSort {
+    Name,
+    Title,
+    /// Most recently modified first
+    Modified,
+    /// Most recently created first
+    Created,
+    /// Largest first
+    Size,
+}
+
+impl FromStr for ListSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(ListSort::Name),
+            "title" => Ok(ListSort::Title),
+            "modified" => Ok(ListSort::Modified),
+            "created" => Ok(ListSort::Created),
+            "size" => Ok(ListSort::Size),
+            other => Err(format!(
+                "unknown sort order '{}', expected name, title, modified, created or size",
+                other
+            )),
+        }
+    }
+}
+
+/// A point in time given on the command line, either relative like `7d` or as a date.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeSpec {
+    Ago(Duration),
+    Date(NaiveDate),
+}
+
+impl FromStr for TimeSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Ok(TimeSpec::Date(date));
+        }
+
+        let error = || {
+            format!(
+                "invalid time '{}', expected something like 12h, 7d, 2w or 2026-01-31",
+                s
+            )
+        };
+
+        let unit = s.chars().last().ok_or_else(error)?;
+        let amount: i64 = s[..s.len() - unit.len_utf8()]
+            .parse()
+            .map_err(|_| error())?;
+
+        let duration = match unit {
+            'h' => Duration::hours(amount),
+            'd' => Duration::days(amount),
+            'w' => Duration::weeks(amount),
+            _ => return Err(error()),
+        };
+
+        Ok(TimeSpec::Ago(duration))
+    }
+}
+
+fn local_time(date: NaiveDate, time: NaiveTime) -> DateTime<Local> {
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .unwrap_or_else(Local::now)
+}
+
+impl TimeSpec {
+    /// The earliest point in time this includes. A date starts at midnight.
+    pub fn start(&self) -> DateTime<Local> {
+        match self {
+            TimeSpec::Ago(duration) => Local::now() - *duration,
+            TimeSpec::Date(date) => local_time(*date, NaiveTime::MIN),
+        }
+    }
+
+    /// The latest point in time this includes. A date lasts until the end of the day.
+    pub fn end(&self) -> DateTime<Local> {
+        match self {
+            TimeSpec::Ago(duration) => Local::now() - *duration,
+            TimeSpec::Date(date) => local_time(*date + Duration::days(1), NaiveTime::MIN),
+        }
+    }
+}
+
+/// Parses the timestamps written into front matter, e.g. `2026-01-31T10:00:00+01:00` or
+/// `2026-01-31`.
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Local>> {
+    let value = value.trim();
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Local));
+    }
+
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(time) = chrono::NaiveDateTime::parse_from_str(value, format) {
+            return Local.from_local_datetime(&time).earliest();
+        }
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .map(|date| local_time(date, NaiveTime::MIN))
+}
+
+/// Sorting and filtering for `ls`. Everything but the sort order only applies to notes.
+#[derive(Debug, Default, Clone)]
+pub struct ListOptions {
+    /// Keeps the id order if not set
+    pub sort: Option<ListSort>,
+    pub reverse: bool,
+    pub ext: Vec<String>,
+    pub since: Option<TimeSpec>,
+    pub until: Option<TimeSpec>,
+    pub limit: Option<usize>,
+    pub notes_only: bool,
+    pub folders_only: bool,
+}
+
+fn modified_of(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// When a note was created: the `created` front matter key if it has one, the file creation
+/// time otherwise.
+fn created_of(
+    note: &Note,
+    index: &SearchIndex,
+) -> Result<Option<DateTime<Local>>, ListFolderError> {
+    if let Some(created) = index.created_of(note)?.as_deref().and_then(parse_timestamp) {
+        return Ok(Some(created));
+    }
+
+    Ok(fs::metadata(note.get_path())
+        .and_then(|metadata| metadata.created().or(metadata.modified()))
+        .ok()
+        .map(|time| time.into()))
+}
+
+fn sort_key_order<T: Ord>(a: T, b: T, sort: ListSort) -> Ordering {
+    match sort {
+        // newest and largest first
+        ListSort::Modified | ListSort::Created | ListSort::Size => b.cmp(&a),
+        ListSort::Name | ListSort::Title => a.cmp(&b),
+    }
+}
+
+impl FolderSearchResult {
+    /// Filters and sorts the entries according to `options`.
+    pub fn apply(
+        &mut self,
+        options: &ListOptions,
+        index: &SearchIndex,
+    ) -> Result<(), ListFolderError> {
+        if options.notes_only {
+            self.folders.clear();
+        }
+
+        if options.folders_only {
+            self.notes.clear();
+        }
+
+        if !options.ext.is_empty() {
+            let extensions: Vec<String> = options
+                .ext
+                .iter()
+                .map(|ext| ext.trim_start_matches('.').to_lowercase())
+                .collect();
+
+            self.notes.retain(|note| {
+                note.get_path()
+                    .extension()
+                    .is_some_and(|ext| extensions.contains(&ext.to_string_lossy().to_lowercase()))
+            });
+        }
+
+        let since = options.since.map(|since| since.start());
+        let until = options.until.map(|until| until.end());
+
+        if since.is_some() || until.is_some() {
+            self.notes.retain(|note| {
+                let modified: Option<DateTime<Local>> =
+                    modified_of(&note.get_path()).map(|time| time.into());
+
+                modified.is_some_and(|modified| {
+                    since.is_none_or(|since| modified >= since)
+                        && until.is_none_or(|until| modified < until)
+                })
+            });
+        }
+
+        if let Some(sort) = options.sort {
+            self.sort_notes(sort, index)?;
+            self.sort_folders(sort);
+        }
+
+        if options.reverse {
+            self.folders.reverse();
+            self.notes.reverse();
+        }
+
+        if let Some(limit) = options.limit {
+            self.notes.truncate(limit);
+        }
+
+        Ok(())
+    }
+
+    fn sort_notes(&mut self, sort: ListSort, index: &SearchIndex) -> Result<(), ListFolderError> {
+        let mut keyed: Vec<(Note, String, Option<DateTime<Local>>, u64)> = Vec::new();
+
+        for note in self.notes.drain(..) {
+            let name = match sort {
+                ListSort::Title => index.name_of(&note)?.to_lowercase(),
+                _ => note.name.to_lowercase(),
+            };
+
+            let time = match sort {
+                ListSort::Modified => modified_of(&note.get_path()).map(|time| time.into()),
+                ListSort::Created => created_of(&note, index)?,
+                _ => None,
+            };
+
+            let size = fs::metadata(note.get_path())
+                .map(|metadata| metadata.len())
+                .unwrap_or(0);
+
+            keyed.push((note, name, time, size));
+        }
+
+        keyed.sort_by(|(_, a_name, a_time, a_size), (_, b_name, b_time, b_size)| {
+            let order = match sort {
+                ListSort::Name | ListSort::Title => Ordering::Equal,
+                ListSort::Modified | ListSort::Created => sort_key_order(a_time, b_time, sort),
+                ListSort::Size => sort_key_order(a_size, b_size, sort),
+            };
+
+            order.then_with(|| a_name.cmp(b_name))
+        });
+
+        self.notes = keyed.into_iter().map(|(note, ..)| note).collect();
+
+        Ok(())
+    }
+
+    fn sort_folders(&mut self, sort: ListSort) {
+        let name = |folder: &Folder| folder.name.to_lowercase();
+
+        match sort {
+            ListSort::Modified => self.folders.sort_by(|a, b| {
+                sort_key_order(modified_of(&a.get_path()), modified_of(&b.get_path()), sort)
+                    .then_with(|| name(a).cmp(&name(b)))
+            }),
+            _ => self.folders.sort_by_key(name),
+        }
+    }
+}
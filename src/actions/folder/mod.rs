@@ -1,7 +1,17 @@
 pub mod create;
 pub mod get_notes_by_name;
+pub mod index;
+pub mod links;
 pub mod list;
+pub mod list_options;
 pub mod model;
 pub mod remove;
+pub mod rename;
+pub mod search_matcher;
 pub mod search_notes;
+pub mod search_query;
 pub mod sync;
+pub mod tags;
+pub mod tasks;
+pub mod todos;
+pub mod tree;
@@ -1,24 +1,128 @@
-use std::fs::remove_dir;
-
 use colored::Colorize;
 use thiserror::Error;
 
-use crate::actions::folder::model::Folder;
+use crate::{
+    actions::{
+        folder::{
+            list::ListFolderError,
+            model::Folder,
+            sync::{
+                mutation::{Mutation, MutationAction},
+                sync_note::SyncError,
+            },
+        },
+        trash::model::{Trash, TrashError, TrashItem, TrashKind},
+    },
+    config::model::Config,
+    output,
+};
 
 #[derive(Error, Debug)]
 pub enum RemoveFolderError {
+    #[error("the folder {0} does not exist!")]
+    FolderDoesNotExist(String),
+
+    #[error("the folder {0} is not empty, use --recursive to remove it with everything in it")]
+    NotEmpty(String),
+
+    #[error("the folder {0} is a git repository, use --force to remove it anyway")]
+    GitRoot(String),
+
+    #[error(transparent)]
+    ListFolder(#[from] ListFolderError),
+
+    #[error(transparent)]
+    Trash(#[from] TrashError),
+
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+
     #[error(transparent)]
     Io(#[from] std::io::Error),
 }
 
+type Error = RemoveFolderError;
+
 impl Folder {
-    pub fn remove(&self) -> Result<(), RemoveFolderError> {
+    /// Counts the notes and folders below this folder.
+    fn count_recursive(&self) -> Result<(usize, usize), Error> {
+        let entries = self.list()?;
+        let mut notes = entries.notes.len();
+        let mut folders = entries.folders.len();
+
+        for folder in entries.folders.iter() {
+            let (sub_notes, sub_folders) = folder.count_recursive()?;
+            notes += sub_notes;
+            folders += sub_folders;
+        }
+
+        Ok((notes, folders))
+    }
+
+    /// Moves the folder to the trash after asking and commits the removal. Folders with notes or
+    /// subfolders need `recursive`, git repositories need `force`. Returns the trash item, or
+    /// `None` if the user changed their mind.
+    pub fn remove(
+        &self,
+        config: &Config,
+        recursive: bool,
+        force: bool,
+    ) -> Result<Option<TrashItem>, Error> {
         let path = self.get_path();
 
-        remove_dir(path)?;
+        if !path.is_dir() {
+            return Err(Error::FolderDoesNotExist(self.name.clone()));
+        }
+
+        let is_git_root = path.join(".git").exists();
+        if is_git_root && !force {
+            return Err(Error::GitRoot(self.name.clone()));
+        }
+
+        let (notes, folders) = self.count_recursive()?;
+        if !recursive && notes + folders > 0 {
+            return Err(Error::NotEmpty(self.name.clone()));
+        }
+
+        let contents = match notes + folders {
+            0 => String::new(),
+            _ => format!(
+                " with {} notes and {} subfolders",
+                notes.to_string().yellow(),
+                folders.to_string().yellow()
+            ),
+        };
+
+        let mut input = String::new();
+
+        output::message_inline(&format!(
+            "Are you sure you want to move '{}'{} to the trash? {}/{} ",
+            self.name.blue(),
+            contents,
+            "y".red(),
+            "N".green()
+        ));
+
+        std::io::stdin().read_line(&mut input)?;
+
+        if input.trim() != "y" {
+            output::message("Aborting!");
+            return Ok(None);
+        }
+
+        let item = Trash::new(&config.data_dir).put(&path, TrashKind::Folder, None)?;
+
+        output::message(&format!(
+            "Moved folder '{}' to the trash as {}",
+            self.name.blue(),
+            item.id.yellow()
+        ));
 
-        println!("Successfully removed folder '{}'", self.name.blue());
+        // a removed repository takes its history with it, so there's nothing to commit to
+        if !is_git_root {
+            Mutation::new(MutationAction::RemoveFolder, &self.name, vec![path]).commit(config)?;
+        }
 
-        Ok(())
+        Ok(Some(item))
     }
 }
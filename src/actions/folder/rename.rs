@@ -0,0 +1,132 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+use thiserror::Error;
+
+use crate::{
+    actions::folder::{
+        links::{RewriteLinksError, normalize_path},
+        model::{Folder, FolderError},
+        sync::{
+            mutation::{Mutation, MutationAction},
+            sync_move::SyncMoveError,
+            sync_note::SyncError,
+        },
+    },
+    config::model::Config,
+    output,
+};
+
+#[derive(Error, Debug)]
+pub enum MoveFolderError {
+    #[error("the data directory itself cannot be moved!")]
+    MoveRoot,
+
+    #[error("cannot move {0} into itself!")]
+    IntoItself(String),
+
+    #[error("the destination {0} already exists!")]
+    DestinationExists(String),
+
+    #[error("the folder {0} does not exist!")]
+    DestinationFolderMissing(String),
+
+    #[error("the destination {0} is outside of the data directory!")]
+    OutsideDataDir(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Folder(#[from] FolderError),
+
+    #[error(transparent)]
+    Links(#[from] RewriteLinksError),
+
+    #[error(transparent)]
+    Move(#[from] SyncMoveError),
+
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+}
+
+type Error = MoveFolderError;
+
+impl Folder {
+    /// Moves or renames the folder. `destination` is relative to the data directory. If it is an
+    /// existing folder, this folder is moved into it.
+    pub fn move_to(&self, config: &Config, destination: &str) -> Result<(), Error> {
+        let data_dir = normalize_path(&config.data_dir);
+        let from = normalize_path(&self.get_path());
+
+        if from == data_dir {
+            return Err(Error::MoveRoot);
+        }
+
+        let mut to = normalize_path(&data_dir.join(destination));
+        if !to.starts_with(&data_dir) {
+            return Err(Error::OutsideDataDir(destination.to_string()));
+        }
+
+        if to.is_dir() {
+            to.push(from.file_name().unwrap());
+        }
+
+        let display_name = |path: &PathBuf| {
+            path.strip_prefix(&data_dir)
+                .unwrap_or(path)
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+
+        if to.starts_with(&from) {
+            return Err(Error::IntoItself(display_name(&from)));
+        }
+
+        if to.exists() {
+            return Err(Error::DestinationExists(display_name(&to)));
+        }
+
+        let parent = to.parent().unwrap().to_path_buf();
+        if !parent.is_dir() {
+            return Err(Error::DestinationFolderMissing(display_name(&parent)));
+        }
+
+        let source_parent = Folder::from_pathbuf(from.parent().unwrap(), ".")?;
+        let destination_parent = Folder::from_pathbuf(&parent, ".")?;
+
+        let git_root = source_parent.sync_move(&destination_parent, &from, &to, config)?;
+
+        let toplevel = Folder::from_pathbuf(&config.data_dir, ".")?;
+        let mut changed = toplevel.rewrite_links(&from, &to)?;
+
+        output::message(&format!(
+            "Moved folder '{}' to '{}'",
+            display_name(&from).blue(),
+            display_name(&to).blue()
+        ));
+
+        if !changed.is_empty() {
+            output::message(&format!("Updated links in {} notes", changed.len()));
+        }
+
+        let git_root = match git_root {
+            None => return Ok(()),
+            Some(value) => value,
+        };
+
+        // the destination goes first, it decides which repository records the move
+        changed.insert(0, to.clone());
+        changed.retain(|path| path.exists() && path.starts_with(git_root.get_path()));
+
+        Mutation::new(
+            MutationAction::MoveFolder,
+            format!("{} -> {}", display_name(&from), display_name(&to)),
+            changed,
+        )
+        .commit(config)?;
+
+        Ok(())
+    }
+}
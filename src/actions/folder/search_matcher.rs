@@ -0,0 +1,94 @@
+use std::ops::Range;
+
+use colored::Colorize;
+use regex::{Regex, RegexBuilder};
+
+/// How the search term is interpreted.
+#[derive(Default, Debug, Clone)]
+pub struct SearchOptions {
+    /// Treat the term as a regular expression instead of a literal string
+    pub regex: bool,
+    pub case_sensitive: bool,
+    /// Only match whole words
+    pub word: bool,
+}
+
+/// Finds a search term in lines of text according to [`SearchOptions`].
+pub struct SearchMatcher {
+    term: String,
+    regex: Regex,
+    options: SearchOptions,
+}
+
+impl SearchMatcher {
+    pub fn new(term: &str, options: SearchOptions) -> Result<Self, regex::Error> {
+        let mut pattern = match options.regex {
+            true => term.to_string(),
+            false => regex::escape(term),
+        };
+
+        if options.word {
+            pattern = format!(r"\b(?:{})\b", pattern);
+        }
+
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!options.case_sensitive)
+            .build()?;
+
+        Ok(Self {
+            term: term.to_string(),
+            regex,
+            options,
+        })
+    }
+
+    /// Returns the term if the search index can be used to narrow down the notes to read. This is
+    /// only possible for literal terms, a regex can match anything.
+    pub fn index_term(&self) -> Option<&str> {
+        match self.options.regex {
+            true => None,
+            false => Some(&self.term),
+        }
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        self.regex.is_match(line)
+    }
+
+    /// Returns the byte ranges of every non-empty match in `line`.
+    pub fn find_ranges(&self, line: &str) -> Vec<Range<usize>> {
+        self.regex
+            .find_iter(line)
+            .filter(|found| !found.is_empty())
+            .map(|found| found.range())
+            .collect()
+    }
+}
+
+/// Colors every match of any of the `matchers` in `line`.
+pub fn highlight_all(line: &str, matchers: &[&SearchMatcher]) -> String {
+    let mut ranges: Vec<Range<usize>> = matchers
+        .iter()
+        .flat_map(|matcher| matcher.find_ranges(line))
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+
+    let mut result = String::with_capacity(line.len());
+    let mut last = 0;
+
+    for range in ranges {
+        // overlapping matches of different terms are merged
+        let start = range.start.max(last);
+        if start >= range.end {
+            continue;
+        }
+
+        result.push_str(&line[last..start]);
+        result.push_str(&format!("{}", line[start..range.end].red()));
+        last = range.end;
+    }
+
+    result.push_str(&line[last..]);
+
+    result
+}
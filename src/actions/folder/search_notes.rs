@@ -1,9 +1,22 @@
+use std::{cmp::Ordering, collections::BTreeSet, fs, path::Path, str::FromStr, time::SystemTime};
+
+use age::secrecy::SecretString;
 use colored::Colorize;
+use serde::Serialize;
 use thiserror::Error;
 
-use crate::actions::{
-    folder::{list::ListFolderError, model::Folder},
-    note::model::{Note, NoteError},
+use crate::{
+    actions::{
+        folder::{
+            list::ListFolderError,
+            model::Folder,
+            search_matcher::highlight_all,
+            search_query::{QueryDocument, SearchQuery},
+        },
+        note::model::{Note, NoteError},
+        search_index::{model::SearchIndex, rank::Ranker},
+    },
+    output,
 };
 
 #[derive(Error, Debug)]
@@ -15,37 +28,195 @@ pub enum SearchNotesError {
     ReadError(#[from] NoteError),
 }
 
+/// A line that matched the query, without any highlighting.
+pub struct SearchMatch {
+    pub line: usize,
+    /// The column of the first match on the line, counted in characters from 1
+    pub column: usize,
+    pub text: String,
+}
+
 pub struct SearchNoteResult {
     pub note: Note,
     pub snippets: Vec<String>,
+    pub matches: Vec<SearchMatch>,
+    pub score: f64,
+}
+
+/// A single search hit in the structured output formats. Notes that only matched through fields
+/// like `title:` have no line.
+#[derive(Serialize)]
+pub struct SearchEntry {
+    pub note: String,
+    pub title: Option<String>,
+    pub score: f64,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub snippet: Option<String>,
+}
+
+impl SearchNoteResult {
+    pub fn entries(&self, root: &Path, index: &SearchIndex) -> Result<Vec<SearchEntry>, NoteError> {
+        let note = output::relative_path(&self.note.get_path(), root);
+        let title = index.title_of(&self.note)?;
+
+        let entry = |found: Option<&SearchMatch>| SearchEntry {
+            note: note.clone(),
+            title: title.clone(),
+            score: self.score,
+            line: found.map(|found| found.line),
+            column: found.map(|found| found.column),
+            snippet: found.map(|found| found.text.clone()),
+        };
+
+        Ok(match self.matches.is_empty() {
+            true => vec![entry(None)],
+            false => self
+                .matches
+                .iter()
+                .map(|found| entry(Some(found)))
+                .collect(),
+        })
+    }
+}
+
+/// The order search results are shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SearchSort {
+    /// Best match first
+    #[default]
+    Relevance,
+    /// Most recently modified first
+    Modified,
+    Path,
+}
+
+impl FromStr for SearchSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "relevance" => Ok(SearchSort::Relevance),
+            "modified" => Ok(SearchSort::Modified),
+            "path" => Ok(SearchSort::Path),
+            other => Err(format!(
+                "unknown sort order '{}', expected relevance, modified or path",
+                other
+            )),
+        }
+    }
+}
+
+fn modified_of(note: &Note) -> Option<SystemTime> {
+    fs::metadata(note.get_path())
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Sorts `results` by `sort`. Ties are broken by path so that the order is stable.
+pub fn sort_search_results(results: &mut [SearchNoteResult], sort: SearchSort) {
+    match sort {
+        SearchSort::Relevance => results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.note.get_path().cmp(&b.note.get_path()))
+        }),
+        SearchSort::Modified => results.sort_by(|a, b| {
+            modified_of(&b.note)
+                .cmp(&modified_of(&a.note))
+                .then_with(|| a.note.get_path().cmp(&b.note.get_path()))
+        }),
+        SearchSort::Path => results.sort_by_key(|result| result.note.get_path()),
+    }
 }
 
 impl Folder {
+    /// Returns all notes below this folder that match `query`, with snippets of the lines that
+    /// contain its terms. Only notes that the search index lists as possible matches are read.
+    /// Every result is scored with BM25, see [`sort_search_results`] for ordering them.
     pub fn search_notes_content(
         &self,
-        term: &str,
+        query: &SearchQuery,
+        tags: &[String],
+        index: &SearchIndex,
+        passphrase: Option<&SecretString>,
+    ) -> Result<Vec<SearchNoteResult>, SearchNotesError> {
+        let candidates = query.candidates(index);
+        let ranker = index.ranker(&query.ranking_terms());
+        self.search_notes_content_in(query, tags, index, &candidates, &ranker, passphrase)
+    }
+
+    fn search_notes_content_in(
+        &self,
+        query: &SearchQuery,
+        tags: &[String],
+        index: &SearchIndex,
+        candidates: &Option<BTreeSet<String>>,
+        ranker: &Ranker,
+        passphrase: Option<&SecretString>,
     ) -> Result<Vec<SearchNoteResult>, SearchNotesError> {
         let entries = self.list()?;
         let mut matching_notes: Vec<SearchNoteResult> = Vec::new();
+        let matchers = query.highlight_matchers();
+
         for note in entries.notes.iter() {
-            let lines = note.get_content_by_lines()?;
+            // the index knows nothing about the content of encrypted notes, so they can't be skipped
+            if (!note.is_encrypted() && index.can_skip(note, candidates))
+                || !index.has_tags(note, tags)?
+            {
+                continue;
+            }
+
+            let document = match (note.is_encrypted(), passphrase) {
+                (false, _) => QueryDocument::new(note, index),
+                (true, None) => continue,
+                (true, Some(passphrase)) => match note.decrypt_content(passphrase) {
+                    Ok(content) => QueryDocument::with_content(note, index, content),
+                    Err(e) => {
+                        output::message(&format!(
+                            "{} '{}': {}",
+                            "Skipping".yellow(),
+                            note.name.blue(),
+                            e
+                        ));
+                        continue;
+                    }
+                },
+            };
+            if !query.matches(&document)? {
+                continue;
+            }
+
+            let lines = document.content()?.lines().map(|line| line.to_string());
             let mut search_result = SearchNoteResult {
                 note: note.clone(),
                 snippets: Vec::new(),
+                matches: Vec::new(),
+                score: 0.0,
             };
 
             let mut line_before: Option<String> = None;
             let mut line_number = 0;
 
-            for line in lines.map_while(Result::ok) {
+            for line in lines {
                 line_number += 1;
 
-                if line.to_lowercase().contains(term) {
-                    let index = line.to_lowercase().find(term).unwrap();
-                    let (_, correct_word) = line.split_at(index);
-                    let (correct_word, _) = correct_word.split_at(term.len());
-                    let correct_word_color = format!("{}", correct_word.red());
-                    let line = line.replace(correct_word, &correct_word_color);
+                if matchers.iter().any(|matcher| matcher.is_match(&line)) {
+                    let start = matchers
+                        .iter()
+                        .flat_map(|matcher| matcher.find_ranges(&line))
+                        .map(|range| range.start)
+                        .min()
+                        .unwrap_or(0);
+
+                    search_result.matches.push(SearchMatch {
+                        line: line_number,
+                        column: line[..start].chars().count() + 1,
+                        text: line.clone(),
+                    });
+
+                    let line = highlight_all(&line, &matchers);
 
                     let snippet = {
                         let snippet = format!(
@@ -76,15 +247,97 @@ impl Folder {
                 }
             }
 
-            if !search_result.snippets.is_empty() {
-                matching_notes.push(search_result);
-            }
+            // without terms to rank by, e.g. for regular expressions, more matching lines rank higher
+            search_result.score = match ranker.has_terms() {
+                true => ranker.score(note),
+                false => search_result.snippets.len() as f64,
+            };
+
+            matching_notes.push(search_result);
         }
 
         for folder in entries.folders.iter() {
-            let mut sub_notes = folder.search_notes_content(term)?;
+            let mut sub_notes = folder
+                .search_notes_content_in(query, tags, index, candidates, ranker, passphrase)?;
             matching_notes.append(&mut sub_notes);
         }
         Ok(matching_notes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn result(path: &str, name: &str, score: f64) -> SearchNoteResult {
+        SearchNoteResult {
+            note: Note {
+                path: path.to_string(),
+                name: name.to_string(),
+                id: None,
+            },
+            snippets: Vec::new(),
+            matches: Vec::new(),
+            score,
+        }
+    }
+
+    fn names(results: &[SearchNoteResult]) -> Vec<&str> {
+        results
+            .iter()
+            .map(|result| result.note.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn path_sort_orders_notes_in_one_folder() {
+        let mut results = vec![
+            result("work", "zeta.md", 1.0),
+            result("work", "alpha.md", 1.0),
+        ];
+
+        sort_search_results(&mut results, SearchSort::Path);
+
+        assert_eq!(names(&results), vec!["alpha.md", "zeta.md"]);
+    }
+
+    #[test]
+    fn path_sort_interleaves_subfolders() {
+        // the search lists a folder's notes before the ones of its subfolders
+        let mut results = vec![
+            result("work", "a.md", 1.0),
+            result("work", "c.md", 1.0),
+            result("work/b", "x.md", 1.0),
+        ];
+
+        sort_search_results(&mut results, SearchSort::Path);
+
+        let paths: Vec<PathBuf> = results
+            .iter()
+            .map(|result| result.note.get_path())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("work/a.md"),
+                PathBuf::from("work/b/x.md"),
+                PathBuf::from("work/c.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn equal_scores_are_ordered_by_path() {
+        let mut results = vec![
+            result("work", "zeta.md", 2.0),
+            result("work", "beta.md", 1.0),
+            result("work", "alpha.md", 2.0),
+        ];
+
+        sort_search_results(&mut results, SearchSort::Relevance);
+
+        assert_eq!(names(&results), vec!["alpha.md", "zeta.md", "beta.md"]);
+    }
+}
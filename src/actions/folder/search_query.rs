@@ -0,0 +1,588 @@
+use std::{cell::OnceCell, collections::BTreeSet, fs};
+
+use chrono::{DateTime, Local, NaiveDate};
+use thiserror::Error;
+
+use crate::actions::{
+    folder::search_matcher::{SearchMatcher, SearchOptions},
+    note::{
+        model::{Note, NoteError},
+        tags::{normalize_tag, tag_matches},
+    },
+    search_index::model::{SearchIndex, tokenize as index_tokenize},
+};
+
+#[derive(Error, Debug)]
+#[error("{message} at column {column}:\n  {query}\n  {caret}")]
+pub struct QueryParseError {
+    pub message: String,
+    /// 1-based character column of the problem
+    pub column: usize,
+    query: String,
+    caret: String,
+}
+
+impl QueryParseError {
+    fn new(query: &str, position: usize, message: impl ToString) -> Self {
+        let column = query[..position.min(query.len())].chars().count();
+
+        Self {
+            message: message.to_string(),
+            column: column + 1,
+            query: query.to_string(),
+            caret: format!("{}^", " ".repeat(column)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Before,
+    BeforeOrOn,
+    On,
+    AfterOrOn,
+    After,
+}
+
+/// A parsed search query.
+enum QueryNode {
+    /// Matches notes containing the term anywhere in their content
+    Content(SearchMatcher),
+    Title(String),
+    Path(String),
+    Tag(String),
+    Ext(String),
+    Modified(Comparison, NaiveDate),
+    And(Box<QueryNode>, Box<QueryNode>),
+    Or(Box<QueryNode>, Box<QueryNode>),
+    Not(Box<QueryNode>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Phrase(String),
+    Field(String, String),
+    And,
+    Or,
+    Not,
+    LeftParen,
+    RightParen,
+}
+
+const FIELDS: &[&str] = &["title", "path", "tag", "ext", "modified"];
+
+fn read_phrase(query: &str, start: usize) -> Result<(String, usize), QueryParseError> {
+    // `start` points at the opening quote
+    let rest = &query[start + 1..];
+    match rest.find('"') {
+        None => Err(QueryParseError::new(query, start, "unterminated quote")),
+        Some(0) => Err(QueryParseError::new(query, start, "empty phrase")),
+        Some(end) => Ok((rest[..end].to_string(), start + 1 + end + 1)),
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<(Token, usize)>, QueryParseError> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while position < query.len() {
+        let c = query[position..].chars().next().unwrap();
+
+        if c.is_whitespace() {
+            position += c.len_utf8();
+            continue;
+        }
+
+        match c {
+            '(' => {
+                tokens.push((Token::LeftParen, position));
+                position += 1;
+            }
+            ')' => {
+                tokens.push((Token::RightParen, position));
+                position += 1;
+            }
+            '"' => {
+                let (phrase, end) = read_phrase(query, position)?;
+                tokens.push((Token::Phrase(phrase), position));
+                position = end;
+            }
+            _ => {
+                let start = position;
+                let end = query[start..]
+                    .find(|c: char| c.is_whitespace() || c == '(' || c == ')' || c == '"')
+                    .map(|end| start + end)
+                    .unwrap_or(query.len());
+                let word = &query[start..end];
+                position = end;
+
+                let field = word
+                    .split_once(':')
+                    .filter(|(name, _)| FIELDS.contains(name));
+
+                let token = match field {
+                    Some((name, "")) if query[end..].starts_with('"') => {
+                        let (value, phrase_end) = read_phrase(query, end)?;
+                        position = phrase_end;
+                        Token::Field(name.to_string(), value)
+                    }
+                    Some((name, "")) => {
+                        return Err(QueryParseError::new(
+                            query,
+                            start,
+                            format!("missing value for '{}:'", name),
+                        ));
+                    }
+                    Some((name, value)) => Token::Field(name.to_string(), value.to_string()),
+                    None => match word {
+                        "AND" => Token::And,
+                        "OR" => Token::Or,
+                        "NOT" => Token::Not,
+                        _ => Token::Word(word.to_string()),
+                    },
+                };
+
+                tokens.push((token, start));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// A recursive descent parser for
+/// ```text
+/// or      := and ("OR" and)*
+/// and     := unary ("AND"? unary)*
+/// unary   := "NOT" unary | primary
+/// primary := "(" or ")" | WORD | "PHRASE" | FIELD:VALUE
+/// ```
+struct Parser<'a> {
+    query: &'a str,
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    options: &'a SearchOptions,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map(|(_, offset)| *offset)
+            .unwrap_or(self.query.len())
+    }
+
+    fn error(&self, message: impl ToString) -> QueryParseError {
+        QueryParseError::new(self.query, self.offset(), message)
+    }
+
+    fn parse_or(&mut self) -> Result<QueryNode, QueryParseError> {
+        let mut node = self.parse_and()?;
+
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            node = QueryNode::Or(Box::new(node), Box::new(self.parse_and()?));
+        }
+
+        Ok(node)
+    }
+
+    fn parse_and(&mut self) -> Result<QueryNode, QueryParseError> {
+        let mut node = self.parse_unary()?;
+
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.position += 1;
+                }
+                Some(Token::Word(_))
+                | Some(Token::Phrase(_))
+                | Some(Token::Field(_, _))
+                | Some(Token::Not)
+                | Some(Token::LeftParen) => {}
+                _ => break,
+            }
+
+            node = QueryNode::And(Box::new(node), Box::new(self.parse_unary()?));
+        }
+
+        Ok(node)
+    }
+
+    fn parse_unary(&mut self) -> Result<QueryNode, QueryParseError> {
+        if self.peek() == Some(&Token::Not) {
+            self.position += 1;
+            return Ok(QueryNode::Not(Box::new(self.parse_unary()?)));
+        }
+
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<QueryNode, QueryParseError> {
+        let offset = self.offset();
+
+        let token = match self.peek() {
+            None => return Err(self.error("expected a search term")),
+            Some(token) => token.clone(),
+        };
+
+        match token {
+            Token::LeftParen => {
+                self.position += 1;
+                let node = self.parse_or()?;
+
+                if self.peek() != Some(&Token::RightParen) {
+                    return Err(QueryParseError::new(self.query, offset, "unclosed '('"));
+                }
+
+                self.position += 1;
+                Ok(node)
+            }
+            Token::Word(term) | Token::Phrase(term) => {
+                self.position += 1;
+                let matcher = SearchMatcher::new(&term, self.options.clone())
+                    .map_err(|e| QueryParseError::new(self.query, offset, e))?;
+                Ok(QueryNode::Content(matcher))
+            }
+            Token::Field(name, value) => {
+                self.position += 1;
+                self.parse_field(&name, &value, offset)
+            }
+            Token::RightParen => Err(self.error("unexpected ')'")),
+            Token::And | Token::Or => Err(self.error("expected a search term before the operator")),
+            Token::Not => unreachable!("handled by parse_unary"),
+        }
+    }
+
+    fn parse_field(
+        &self,
+        name: &str,
+        value: &str,
+        offset: usize,
+    ) -> Result<QueryNode, QueryParseError> {
+        let value_offset = offset + name.len() + 1;
+
+        Ok(match name {
+            "title" => QueryNode::Title(value.to_lowercase()),
+            "path" => QueryNode::Path(value.to_lowercase()),
+            "ext" => QueryNode::Ext(value.trim_start_matches('.').to_lowercase()),
+            "tag" => match normalize_tag(value) {
+                Some(tag) => QueryNode::Tag(tag),
+                None => {
+                    return Err(QueryParseError::new(
+                        self.query,
+                        value_offset,
+                        "invalid tag",
+                    ));
+                }
+            },
+            "modified" => {
+                let (comparison, date) = if let Some(date) = value.strip_prefix(">=") {
+                    (Comparison::AfterOrOn, date)
+                } else if let Some(date) = value.strip_prefix("<=") {
+                    (Comparison::BeforeOrOn, date)
+                } else if let Some(date) = value.strip_prefix('>') {
+                    (Comparison::After, date)
+                } else if let Some(date) = value.strip_prefix('<') {
+                    (Comparison::Before, date)
+                } else {
+                    (Comparison::On, value.strip_prefix('=').unwrap_or(value))
+                };
+
+                let date_offset = value_offset + (value.len() - date.len());
+                let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+                    QueryParseError::new(self.query, date_offset, "expected a date like 2026-01-31")
+                })?;
+
+                QueryNode::Modified(comparison, date)
+            }
+            _ => unreachable!("only known fields are lexed as fields"),
+        })
+    }
+}
+
+/// A note while it is being matched. The content is only read if the query needs it.
+pub struct QueryDocument<'a> {
+    pub note: &'a Note,
+    index: &'a SearchIndex,
+    content: OnceCell<String>,
+}
+
+impl<'a> QueryDocument<'a> {
+    pub fn new(note: &'a Note, index: &'a SearchIndex) -> Self {
+        Self {
+            note,
+            index,
+            content: OnceCell::new(),
+        }
+    }
+
+    /// A document whose content is already known, e.g. because it had to be decrypted.
+    pub fn with_content(note: &'a Note, index: &'a SearchIndex, content: String) -> Self {
+        Self {
+            note,
+            index,
+            content: OnceCell::from(content),
+        }
+    }
+
+    pub fn content(&self) -> Result<&str, NoteError> {
+        if let Some(content) = self.content.get() {
+            return Ok(content);
+        }
+
+        let content = self.note.get_content()?;
+        Ok(self.content.get_or_init(|| content))
+    }
+}
+
+impl QueryNode {
+    fn matches(&self, document: &QueryDocument) -> Result<bool, NoteError> {
+        Ok(match self {
+            QueryNode::Content(matcher) => document
+                .content()?
+                .lines()
+                .any(|line| matcher.is_match(line)),
+            QueryNode::Title(title) => document
+                .index
+                .name_of(document.note)?
+                .to_lowercase()
+                .contains(title),
+            QueryNode::Path(path) => document
+                .index
+                .key_of(document.note)
+                .unwrap_or(document.note.name.clone())
+                .to_lowercase()
+                .contains(path),
+            QueryNode::Tag(filter) => document
+                .index
+                .tags_of(document.note)?
+                .iter()
+                .any(|tag| tag_matches(tag, filter)),
+            QueryNode::Ext(ext) => document
+                .note
+                .get_path()
+                .extension()
+                .is_some_and(|note_ext| note_ext.to_string_lossy().to_lowercase() == *ext),
+            QueryNode::Modified(comparison, date) => {
+                let modified: DateTime<Local> =
+                    fs::metadata(document.note.get_path())?.modified()?.into();
+                let modified = modified.date_naive();
+
+                match comparison {
+                    Comparison::Before => modified < *date,
+                    Comparison::BeforeOrOn => modified <= *date,
+                    Comparison::On => modified == *date,
+                    Comparison::AfterOrOn => modified >= *date,
+                    Comparison::After => modified > *date,
+                }
+            }
+            QueryNode::And(left, right) => left.matches(document)? && right.matches(document)?,
+            QueryNode::Or(left, right) => left.matches(document)? || right.matches(document)?,
+            QueryNode::Not(node) => !node.matches(document)?,
+        })
+    }
+
+    /// Collects the content terms that make a note match, i.e. the ones that aren't negated.
+    fn positive_matchers<'a>(&'a self, negated: bool, matchers: &mut Vec<&'a SearchMatcher>) {
+        match self {
+            QueryNode::Content(matcher) if !negated => matchers.push(matcher),
+            QueryNode::And(left, right) | QueryNode::Or(left, right) => {
+                left.positive_matchers(negated, matchers);
+                right.positive_matchers(negated, matchers);
+            }
+            QueryNode::Not(node) => node.positive_matchers(!negated, matchers),
+            _ => {}
+        }
+    }
+
+    /// Returns the notes that can possibly match according to the index, or `None` if the index
+    /// can't narrow this part of the query down.
+    fn candidates(&self, index: &SearchIndex) -> Option<BTreeSet<String>> {
+        match self {
+            QueryNode::Content(matcher) => index.candidates(matcher.index_term()?),
+            QueryNode::And(left, right) => {
+                match (left.candidates(index), right.candidates(index)) {
+                    (Some(left), Some(right)) => Some(left.intersection(&right).cloned().collect()),
+                    (Some(only), None) | (None, Some(only)) => Some(only),
+                    (None, None) => None,
+                }
+            }
+            QueryNode::Or(left, right) => {
+                let mut left = left.candidates(index)?;
+                left.extend(right.candidates(index)?);
+                Some(left)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A search query, either parsed from the query language or a single literal/regex term.
+pub struct SearchQuery {
+    root: QueryNode,
+}
+
+impl SearchQuery {
+    /// Parses a query like `kubernetes AND outage NOT draft` or `title:retro "action items"`.
+    /// Plain words and phrases are matched against the content using `options`.
+    pub fn parse(query: &str, options: SearchOptions) -> Result<Self, QueryParseError> {
+        let mut parser = Parser {
+            query,
+            tokens: tokenize(query)?,
+            position: 0,
+            options: &options,
+        };
+
+        let root = parser.parse_or()?;
+
+        if parser.peek().is_some() {
+            return Err(match parser.peek() {
+                Some(Token::RightParen) => parser.error("unmatched ')'"),
+                _ => parser.error("unexpected token"),
+            });
+        }
+
+        Ok(Self { root })
+    }
+
+    /// Uses the whole term as a single content matcher without interpreting any query syntax.
+    pub fn literal(term: &str, options: SearchOptions) -> Result<Self, regex::Error> {
+        Ok(Self {
+            root: QueryNode::Content(SearchMatcher::new(term, options)?),
+        })
+    }
+
+    pub fn matches(&self, document: &QueryDocument) -> Result<bool, NoteError> {
+        self.root.matches(document)
+    }
+
+    /// The content terms that should be shown and highlighted in the results.
+    pub fn highlight_matchers(&self) -> Vec<&SearchMatcher> {
+        let mut matchers = Vec::new();
+        self.root.positive_matchers(false, &mut matchers);
+        matchers
+    }
+
+    pub fn candidates(&self, index: &SearchIndex) -> Option<BTreeSet<String>> {
+        self.root.candidates(index)
+    }
+
+    /// The index terms the results are ranked by. Regular expressions don't contribute any.
+    pub fn ranking_terms(&self) -> Vec<String> {
+        let mut terms: Vec<String> = Vec::new();
+
+        for matcher in self.highlight_matchers() {
+            for term in matcher.index_term().into_iter().flat_map(index_tokenize) {
+                if !terms.contains(&term) {
+                    terms.push(term);
+                }
+            }
+        }
+
+        terms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The parsed query with explicit parentheses, e.g. `(a OR (b AND c))`.
+    fn describe(node: &QueryNode) -> String {
+        match node {
+            QueryNode::Content(matcher) => matcher.index_term().unwrap_or_default().to_string(),
+            QueryNode::Title(value) => format!("title:{}", value),
+            QueryNode::Path(value) => format!("path:{}", value),
+            QueryNode::Tag(value) => format!("tag:{}", value),
+            QueryNode::Ext(value) => format!("ext:{}", value),
+            QueryNode::Modified(comparison, date) => format!("modified:{:?}:{}", comparison, date),
+            QueryNode::And(left, right) => format!("({} AND {})", describe(left), describe(right)),
+            QueryNode::Or(left, right) => format!("({} OR {})", describe(left), describe(right)),
+            QueryNode::Not(node) => format!("NOT {}", describe(node)),
+        }
+    }
+
+    fn parse(query: &str) -> String {
+        describe(
+            &SearchQuery::parse(query, SearchOptions::default())
+                .unwrap()
+                .root,
+        )
+    }
+
+    fn error_column(query: &str) -> usize {
+        match SearchQuery::parse(query, SearchOptions::default()) {
+            Ok(_) => panic!("'{}' should not parse", query),
+            Err(e) => e.column,
+        }
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(parse("a OR b c"), "(a OR (b AND c))");
+        assert_eq!(
+            parse("kubernetes AND outage NOT draft"),
+            "((kubernetes AND outage) AND NOT draft)"
+        );
+        assert_eq!(parse("(a OR b) c"), "((a OR b) AND c)");
+    }
+
+    #[test]
+    fn adjacent_terms_are_combined_with_and() {
+        assert_eq!(parse("a b \"c d\""), "((a AND b) AND c d)");
+    }
+
+    #[test]
+    fn field_values_can_be_quoted() {
+        assert_eq!(
+            parse("title:\"Action Items\" tag:ops"),
+            "(title:action items AND tag:ops)"
+        );
+    }
+
+    #[test]
+    fn modified_comparisons() {
+        assert_eq!(parse("modified:>2026-01-01"), "modified:After:2026-01-01");
+        assert_eq!(
+            parse("modified:>=2026-01-01"),
+            "modified:AfterOrOn:2026-01-01"
+        );
+        assert_eq!(parse("modified:<2026-01-01"), "modified:Before:2026-01-01");
+        assert_eq!(
+            parse("modified:<=2026-01-01"),
+            "modified:BeforeOrOn:2026-01-01"
+        );
+        assert_eq!(parse("modified:=2026-01-01"), "modified:On:2026-01-01");
+        assert_eq!(parse("modified:2026-01-01"), "modified:On:2026-01-01");
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        // columns are 1-based
+        assert_eq!(error_column("a \"open"), 3);
+        assert_eq!(error_column("a (b OR c"), 3);
+        assert_eq!(error_column("a b)"), 4);
+        assert_eq!(error_column("x tag:"), 3);
+        assert_eq!(error_column("x tag:#"), 7);
+        assert_eq!(error_column("modified:>2026-13-01"), 11);
+        assert_eq!(error_column("modified:soon"), 10);
+    }
+
+    #[test]
+    fn error_message_shows_a_caret() {
+        let error = SearchQuery::parse("a b)", SearchOptions::default())
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            error.to_string(),
+            "unmatched ')' at column 4:\n  a b)\n     ^"
+        );
+    }
+}
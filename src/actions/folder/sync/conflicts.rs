@@ -0,0 +1,529 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use thiserror::Error;
+use tracing::debug;
+
+use crate::{
+    actions::{
+        folder::{
+            index::{INDEX_FILE_NAME, IndexError, NoteIndex},
+            model::{Folder, FolderError},
+            sync::{
+                git::{Conflict, GitError, GitRepository, PullStatus},
+                merge::{MARKDOWN_EXTENSION, merge_notes},
+            },
+        },
+        note::{encryption::ENCRYPTED_EXTENSION, open::run_editor},
+    },
+    config::model::Config,
+    output::{self, ProgressLine},
+};
+
+/// How often a rejected push is retried after pulling the remote changes.
+const PUSH_ATTEMPTS: usize = 3;
+
+#[derive(Error, Debug)]
+pub enum ConflictError {
+    #[error(
+        "stopped merging, the local changes are committed but not pushed. Run sync to try again"
+    )]
+    Aborted,
+
+    #[error("failed to run the editor: {0}")]
+    Editor(String),
+
+    #[error("the remote kept changing, gave up pushing after {0} tries")]
+    TooManyAttempts(usize),
+
+    #[error(transparent)]
+    Git(#[from] GitError),
+
+    #[error(transparent)]
+    Index(#[from] IndexError),
+
+    #[error(transparent)]
+    Folder(#[from] FolderError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+type Error = ConflictError;
+
+/// What to do with a note that was changed on both sides.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Resolution {
+    Local,
+    Remote,
+    Both,
+    Edit,
+    Abort,
+}
+
+fn is_index(path: &str) -> bool {
+    Path::new(path)
+        .file_name()
+        .is_some_and(|name| name == INDEX_FILE_NAME)
+}
+
+/// Only the labelled markers count, a line of `=` is also a markdown heading underline.
+fn has_conflict_markers(content: &str) -> bool {
+    content
+        .lines()
+        .any(|line| line.starts_with("<<<<<<< ") || line.starts_with(">>>>>>> "))
+}
+
+/// Writes one side of a conflict, or removes the file if that side removed it.
+fn write_side(path: &Path, content: &Option<Vec<u8>>) -> Result<(), Error> {
+    match content {
+        Some(content) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, content)?;
+        }
+        None if path.exists() => fs::remove_file(path)?,
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// A free path next to `path` for the remote version, e.g. `meeting-remote.md`.
+fn remote_copy_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap().to_str().unwrap();
+    // a leading dot is part of the name, not the extension
+    let (stem, extension) = match name.char_indices().skip(1).find(|(_, c)| *c == '.') {
+        Some((position, _)) => name.split_at(position),
+        None => (name, ""),
+    };
+
+    let mut copy = path.with_file_name(format!("{}-remote{}", stem, extension));
+    let mut counter = 2;
+    while copy.exists() {
+        copy = path.with_file_name(format!("{}-remote-{}{}", stem, counter, extension));
+        counter += 1;
+    }
+
+    copy
+}
+
+/// Asks what to do with a conflicted note. Without an answer, the merge is aborted.
+fn ask_resolution(conflict: &Conflict, can_edit: bool) -> Result<Resolution, Error> {
+    let change = match (&conflict.ours, &conflict.theirs) {
+        (None, _) => "was removed here but changed on the remote",
+        (_, None) => "was changed here but removed on the remote",
+        _ => "was changed here and on the remote",
+    };
+
+    let mut options = vec!["[l]ocal", "[r]emote"];
+    if can_edit {
+        options.extend(["[b]oth", "[e]dit"]);
+    }
+    options.push("[a]bort");
+
+    loop {
+        output::message_inline(&format!(
+            "'{}' {}. Keep {}? ",
+            conflict.path.blue(),
+            change,
+            options.join(", ")
+        ));
+
+        let mut input = String::new();
+        if std::io::stdin().read_line(&mut input)? == 0 {
+            output::message("");
+            return Ok(Resolution::Abort);
+        }
+
+        let resolution = match input.trim() {
+            "l" | "local" => Resolution::Local,
+            "r" | "remote" => Resolution::Remote,
+            "b" | "both" if can_edit => Resolution::Both,
+            "e" | "edit" if can_edit => Resolution::Edit,
+            "a" | "abort" => Resolution::Abort,
+            _ => continue,
+        };
+
+        return Ok(resolution);
+    }
+}
+
+impl Folder {
+    /// Pulls the remote changes into this repository, which has to be the git root folder.
+    /// Notes that were changed on both sides are resolved by asking, then the merge is committed.
+    pub fn sync_pull(
+        &self,
+        config: &Config,
+        repository: &dyn GitRepository,
+    ) -> Result<PullStatus, Error> {
+        let progress = ProgressLine::default();
+        let status = repository.pull(&|transfer| progress.update(&transfer.label()));
+        progress.clear();
+
+        match status {
+            Err(GitError::Conflicts(paths)) => {
+                debug!("Pulling conflicted in {:?}", paths);
+                self.sync_resolve(config, repository)?;
+                Ok(PullStatus::Merged)
+            }
+            other => Ok(other?),
+        }
+    }
+
+    /// Pushes this repository. If the remote has moved on, the local commits are rebased onto
+    /// it, or merged if they conflict, and the push is tried again.
+    pub fn sync_push(&self, config: &Config, repository: &dyn GitRepository) -> Result<(), Error> {
+        let progress = ProgressLine::default();
+
+        for attempt in 1..=PUSH_ATTEMPTS {
+            let pushed = repository.push(&|transfer| progress.update(&transfer.label()));
+            progress.clear();
+
+            match pushed {
+                Ok(()) => return Ok(()),
+                Err(GitError::NotFastForward) if attempt == PUSH_ATTEMPTS => break,
+                Err(GitError::NotFastForward) => {
+                    debug!("Push {} was rejected, pulling with rebase", attempt)
+                }
+                Err(e) => return Err(e.into()),
+            }
+
+            let rebased = repository.pull_rebase(&|transfer| progress.update(&transfer.label()));
+            progress.clear();
+
+            match rebased {
+                Err(GitError::RebaseConflict) => {
+                    self.sync_pull(config, repository)?;
+                }
+                other => {
+                    other?;
+                }
+            }
+        }
+
+        Err(Error::TooManyAttempts(PUSH_ATTEMPTS))
+    }
+
+    /// Goes through the conflicts of the merge in progress and commits the merge.
+    fn sync_resolve(&self, config: &Config, repository: &dyn GitRepository) -> Result<(), Error> {
+        let mut conflicts = repository.conflicts()?;
+        // indexes first, keeping both versions of a note adds to them
+        conflicts.sort_by_key(|conflict| !is_index(&conflict.path));
+
+        if conflicts.iter().any(|conflict| !is_index(&conflict.path)) {
+            output::message("");
+        }
+
+        let mut unresolved = Vec::new();
+
+        for conflict in conflicts {
+            if is_index(&conflict.path) {
+                self.sync_resolve_index(&conflict)?;
+            } else if self.sync_merge_note(&conflict)? {
+                output::message(&format!("Merged '{}' automatically", conflict.path.blue()));
+            } else {
+                unresolved.push(conflict);
+                continue;
+            }
+
+            repository.stage(std::slice::from_ref(&conflict.path))?;
+        }
+
+        if !unresolved.is_empty() {
+            output::message(&format!(
+                "{} notes were changed here and on the remote",
+                unresolved.len().to_string().yellow()
+            ));
+        }
+
+        // the remote versions kept next to the notes, they aren't part of the merge yet
+        let mut copies = Vec::new();
+
+        for conflict in unresolved.iter() {
+            let paths = match self.sync_resolve_note(config, conflict, &mut copies)? {
+                Some(paths) => paths,
+                None => {
+                    for copy in copies.iter().filter(|copy| copy.exists()) {
+                        fs::remove_file(copy)?;
+                    }
+                    repository.abort_merge()?;
+                    return Err(Error::Aborted);
+                }
+            };
+
+            repository.stage(&paths)?;
+        }
+
+        let name = output::relative_path(&self.get_path(), &config.data_dir);
+        repository.commit(&config.commit_message("Merge", &name))?;
+
+        Ok(())
+    }
+
+    /// Merges a markdown note changed on both sides block by block and writes the result.
+    /// Returns `false` if some blocks were changed on both sides, these are left with conflict
+    /// markers around them.
+    fn sync_merge_note(&self, conflict: &Conflict) -> Result<bool, Error> {
+        let path = self.get_path().join(&conflict.path);
+        if path
+            .extension()
+            .is_none_or(|extension| extension != MARKDOWN_EXTENSION)
+        {
+            return Ok(false);
+        }
+
+        // `None` if the side removed the note, `Some(None)` if it isn't text
+        fn text(content: &Option<Vec<u8>>) -> Option<Option<&str>> {
+            content
+                .as_deref()
+                .map(|content| std::str::from_utf8(content).ok())
+        }
+
+        let merged = match (
+            text(&conflict.ancestor),
+            text(&conflict.ours),
+            text(&conflict.theirs),
+        ) {
+            (Some(Some(ancestor)), Some(Some(ours)), Some(Some(theirs))) => {
+                merge_notes(ancestor, ours, theirs)
+            }
+            // added on both sides, most likely two different notes that got the same name
+            _ => return Ok(false),
+        };
+
+        debug!(
+            "Merged '{}' with {} conflicts",
+            conflict.path, merged.conflicts
+        );
+        fs::write(&path, merged.content)?;
+
+        Ok(merged.conflicts == 0)
+    }
+
+    /// Merges the note ids of both sides, so that no note loses its id.
+    fn sync_resolve_index(&self, conflict: &Conflict) -> Result<(), Error> {
+        let parse = |content: &Option<Vec<u8>>| {
+            content
+                .as_ref()
+                .map(|content| NoteIndex::parse(&String::from_utf8_lossy(content)))
+        };
+
+        let merged = match (parse(&conflict.ours), parse(&conflict.theirs)) {
+            (Some(mut ours), Some(theirs)) => {
+                ours.merge(&parse(&conflict.ancestor).unwrap_or_default(), &theirs);
+                Some(ours)
+            }
+            (ours, theirs) => ours.or(theirs),
+        };
+
+        let path = self.get_path().join(&conflict.path);
+        write_side(&path, &merged.map(|index| index.serialize().into_bytes()))
+    }
+
+    /// Asks what to do with a note that was changed on both sides and does it. Returns the
+    /// paths to stage, or `None` if the merge should be aborted. Copies of the remote version
+    /// are added to `copies`.
+    fn sync_resolve_note(
+        &self,
+        config: &Config,
+        conflict: &Conflict,
+        copies: &mut Vec<PathBuf>,
+    ) -> Result<Option<Vec<String>>, Error> {
+        let path = self.get_path().join(&conflict.path);
+        let encrypted = path
+            .extension()
+            .is_some_and(|extension| extension == ENCRYPTED_EXTENSION);
+        let can_edit = conflict.ours.is_some() && conflict.theirs.is_some() && !encrypted;
+
+        loop {
+            match ask_resolution(conflict, can_edit)? {
+                Resolution::Local => {
+                    write_side(&path, &conflict.ours)?;
+                    return Ok(Some(vec![conflict.path.clone()]));
+                }
+                Resolution::Remote => {
+                    write_side(&path, &conflict.theirs)?;
+                    return Ok(Some(vec![conflict.path.clone()]));
+                }
+                Resolution::Both => {
+                    let copy = remote_copy_path(&path);
+                    write_side(&path, &conflict.ours)?;
+                    write_side(&copy, &conflict.theirs)?;
+                    copies.push(copy.clone());
+
+                    let folder = Folder::from_pathbuf(copy.parent().unwrap(), ".")?;
+                    folder.index_add(copy.file_name().unwrap().to_str().unwrap())?;
+
+                    output::message(&format!(
+                        "Kept the remote version as '{}'",
+                        self.sync_relative_path(&copy).blue()
+                    ));
+
+                    return Ok(Some(vec![
+                        conflict.path.clone(),
+                        self.sync_relative_path(&copy),
+                        self.sync_relative_path(&folder.index_path()),
+                    ]));
+                }
+                Resolution::Edit => {
+                    run_editor(config, &path).map_err(|e| Error::Editor(e.to_string()))?;
+
+                    if has_conflict_markers(&fs::read_to_string(&path)?) {
+                        output::message("The note still has conflict markers");
+                        continue;
+                    }
+
+                    return Ok(Some(vec![conflict.path.clone()]));
+                }
+                Resolution::Abort => return Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::actions::folder::sync::libgit::LibGit;
+
+    fn no_progress(_: crate::actions::folder::sync::git::TransferProgress) {}
+
+    fn clone(directory: &Path, remote: &Path, name: &str) -> LibGit {
+        let repository = LibGit::init(directory, "main").unwrap();
+        let mut git_config = Repository::open(directory).unwrap().config().unwrap();
+        git_config.set_str("user.name", name).unwrap();
+        git_config
+            .set_str("user.email", "test@example.com")
+            .unwrap();
+
+        repository.add_remote(remote.to_str().unwrap()).unwrap();
+        repository.fetch(&no_progress).unwrap();
+        repository.track_branch("main").unwrap();
+        repository
+    }
+
+    fn write_and_commit(repository: &LibGit, directory: &Path, files: &[(&str, Option<&str>)]) {
+        for (path, content) in files {
+            match content {
+                Some(content) => fs::write(directory.join(path), content).unwrap(),
+                None => fs::remove_file(directory.join(path)).unwrap(),
+            }
+        }
+
+        let paths: Vec<String> = files.iter().map(|(path, _)| path.to_string()).collect();
+        repository.stage(&paths).unwrap();
+        repository.commit("Edit").unwrap();
+    }
+
+    #[test]
+    fn only_labelled_lines_are_conflict_markers() {
+        assert!(has_conflict_markers(
+            "<<<<<<< local\nmilk\n=======\neggs\n>>>>>>> remote\n"
+        ));
+        assert!(!has_conflict_markers("Shopping\n=======\n\n- milk\n"));
+    }
+
+    #[test]
+    fn remote_copies_keep_the_extension() {
+        let directory = TempDir::new().unwrap();
+        let note = directory.path().join("meeting.md");
+
+        assert_eq!(
+            remote_copy_path(&note),
+            directory.path().join("meeting-remote.md")
+        );
+        assert_eq!(
+            remote_copy_path(&directory.path().join(".hidden")),
+            directory.path().join(".hidden-remote")
+        );
+        assert_eq!(
+            remote_copy_path(&directory.path().join("archive.tar.gz")),
+            directory.path().join("archive-remote.tar.gz")
+        );
+
+        fs::write(directory.path().join("meeting-remote.md"), "").unwrap();
+        assert_eq!(
+            remote_copy_path(&note),
+            directory.path().join("meeting-remote-2.md")
+        );
+    }
+
+    #[test]
+    fn resolving_the_index_keeps_the_ids_of_both_sides() {
+        let directory = TempDir::new().unwrap();
+        let folder = Folder::new(directory.path().to_str().unwrap(), ".");
+        let conflict = Conflict {
+            path: INDEX_FILE_NAME.to_string(),
+            ancestor: Some(b"a.md\nb.md\n".to_vec()),
+            ours: Some(b"a.md\nb.md\nd.md\n".to_vec()),
+            theirs: Some(b"\nc.md\ne.md\n".to_vec()),
+        };
+
+        folder.sync_resolve_index(&conflict).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(directory.path().join(INDEX_FILE_NAME)).unwrap(),
+            "\nc.md\nd.md\ne.md\n"
+        );
+    }
+
+    #[test]
+    fn pulling_a_conflicting_index_merges_it() {
+        let directory = TempDir::new().unwrap();
+        let remote = directory.path().join("remote.git");
+        Repository::init_bare(&remote).unwrap();
+        let (alice_path, bob_path) = (directory.path().join("alice"), directory.path().join("bob"));
+        let alice = clone(&alice_path, &remote, "Alice");
+        let bob = clone(&bob_path, &remote, "Bob");
+
+        write_and_commit(
+            &alice,
+            &alice_path,
+            &[
+                ("a.md", Some("a\n")),
+                ("b.md", Some("b\n")),
+                (INDEX_FILE_NAME, Some("a.md\nb.md\n")),
+            ],
+        );
+        alice.push(&no_progress).unwrap();
+        bob.pull(&no_progress).unwrap();
+
+        // alice moves b.md while bob adds a note, both change the end of the index
+        write_and_commit(
+            &alice,
+            &alice_path,
+            &[
+                ("b.md", None),
+                ("c.md", Some("b\n")),
+                (INDEX_FILE_NAME, Some("a.md\nc.md\n")),
+            ],
+        );
+        alice.push(&no_progress).unwrap();
+        write_and_commit(
+            &bob,
+            &bob_path,
+            &[
+                ("d.md", Some("d\n")),
+                (INDEX_FILE_NAME, Some("a.md\nb.md\nd.md\n")),
+            ],
+        );
+
+        let config = Config::for_tests(directory.path());
+        let folder = Folder::new(directory.path().to_str().unwrap(), "bob");
+        assert_eq!(folder.sync_pull(&config, &bob).unwrap(), PullStatus::Merged);
+
+        assert_eq!(
+            fs::read_to_string(bob_path.join(INDEX_FILE_NAME)).unwrap(),
+            "a.md\nc.md\nd.md\n"
+        );
+        assert!(bob.conflicts().unwrap().is_empty());
+    }
+}
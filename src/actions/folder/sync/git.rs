@@ -0,0 +1,152 @@
+use thiserror::Error;
+
+use crate::actions::note::history::Revision;
+
+#[derive(Error, Debug)]
+pub enum GitError {
+    #[error("git failed: {0}")]
+    Library(#[from] git2::Error),
+
+    #[error("the repository has no remote named '{0}'")]
+    NoRemote(String),
+
+    #[error("HEAD is detached, check out a branch first")]
+    DetachedHead,
+
+    #[error("unknown revision '{0}'")]
+    UnknownRevision(String),
+
+    #[error("'{path}' does not exist in revision {revision}")]
+    NotInRevision { path: String, revision: String },
+
+    #[error("git doesn't know who you are, set user.name and user.email in your git config")]
+    Identity,
+
+    #[error("the remote rejected '{reference}': {reason}")]
+    PushRejected { reference: String, reason: String },
+
+    #[error("the remote has changes that aren't here yet, pull first")]
+    NotFastForward,
+
+    #[error("pulling left conflicts in: {}", .0.join(", "))]
+    Conflicts(Vec<String>),
+
+    #[error("the local changes don't apply on top of the remote ones")]
+    RebaseConflict,
+
+    #[error("the local and the remote branch have both moved on, sync to merge them")]
+    Diverged,
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// How far a fetch or push has come, e.g. 12 of 40 objects received.
+#[derive(Debug, Clone, Copy)]
+pub struct TransferProgress {
+    pub stage: &'static str,
+    pub current: usize,
+    pub total: usize,
+}
+
+impl TransferProgress {
+    /// Formats the progress as `Receiving objects 30% (12/40)`.
+    pub fn label(&self) -> String {
+        let percent = match self.total {
+            0 => 100,
+            total => self.current * 100 / total,
+        };
+
+        format!(
+            "{} {}% ({}/{})",
+            self.stage, percent, self.current, self.total
+        )
+    }
+}
+
+/// What a pull did to the local branch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PullStatus {
+    UpToDate,
+    FastForward,
+    Merged,
+    Rebased,
+}
+
+/// A file that both sides of a merge changed. A side is `None` if it removed the file.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub path: String,
+    pub ancestor: Option<Vec<u8>>,
+    pub ours: Option<Vec<u8>>,
+    pub theirs: Option<Vec<u8>>,
+}
+
+/// The git operations the sync layer needs. Paths are relative to the root of the repository.
+pub trait GitRepository {
+    /// Adds `origin` pointing to `url`.
+    fn add_remote(&self, url: &str) -> Result<(), GitError>;
+
+    /// Checks out `branch` tracking `origin/<branch>`. If the remote doesn't have the branch
+    /// yet, it's created with the first commit.
+    fn track_branch(&self, branch: &str) -> Result<(), GitError>;
+
+    /// Makes `git` merge files matching `pattern` by running `driver`, configured as the merge
+    /// driver `name` in the repository config and `.git/info/attributes`.
+    fn register_merge_driver(
+        &self,
+        name: &str,
+        driver: &str,
+        pattern: &str,
+    ) -> Result<(), GitError>;
+
+    /// Whether git knows the file, or any file below it for folders.
+    fn is_tracked(&self, path: &str) -> Result<bool, GitError>;
+
+    /// Stages the current state of `paths`, including removals, like `git add -A -- <paths>`.
+    fn stage(&self, paths: &[String]) -> Result<(), GitError>;
+
+    /// Stages every change in the repository.
+    fn stage_all(&self) -> Result<(), GitError>;
+
+    /// Commits what is staged. Returns `false` if there was nothing to commit.
+    fn commit(&self, message: &str) -> Result<bool, GitError>;
+
+    fn fetch(&self, progress: &dyn Fn(TransferProgress)) -> Result<(), GitError>;
+
+    /// Fetches and merges the upstream branch. Conflicts are left in the working tree.
+    fn pull(&self, progress: &dyn Fn(TransferProgress)) -> Result<PullStatus, GitError>;
+
+    /// Fetches and fast-forwards to the upstream branch, without merging. Returns
+    /// [`GitError::Diverged`] if both sides have new commits.
+    fn fast_forward(&self, progress: &dyn Fn(TransferProgress)) -> Result<PullStatus, GitError>;
+
+    /// Fetches and replays the local commits on top of the upstream branch. If that conflicts,
+    /// the rebase is undone and [`GitError::RebaseConflict`] is returned.
+    fn pull_rebase(&self, progress: &dyn Fn(TransferProgress)) -> Result<PullStatus, GitError>;
+
+    /// The files a merge left conflicted.
+    fn conflicts(&self) -> Result<Vec<Conflict>, GitError>;
+
+    /// Throws away a merge in progress and goes back to the last commit.
+    fn abort_merge(&self) -> Result<(), GitError>;
+
+    fn push(&self, progress: &dyn Fn(TransferProgress)) -> Result<(), GitError>;
+
+    /// The commits that changed `path`, newest first. Renames are followed.
+    fn log(&self, path: &str) -> Result<Vec<Revision>, GitError>;
+
+    /// Where the file that is at `path` now was in `revision`, following renames like
+    /// [`GitRepository::log`].
+    fn path_at(&self, path: &str, revision: &str) -> Result<String, GitError>;
+
+    /// The content of `path` in `revision`.
+    fn file_at(&self, revision: &str, path: &str) -> Result<Vec<u8>, GitError>;
+
+    /// The patch of `path` between `from` and `to`, or the working tree without `to`. If the file
+    /// was moved in between, the patch shows the rename.
+    fn diff(&self, from: &str, to: Option<&str>, path: &str) -> Result<String, GitError>;
+
+    /// The patch of `path` that `revision` introduced, including a rename.
+    fn diff_commit(&self, revision: &str, path: &str) -> Result<String, GitError>;
+}
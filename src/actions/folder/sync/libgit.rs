@@ -0,0 +1,1063 @@
+use std::{
+    cell::RefCell,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, FixedOffset};
+use colored::Colorize;
+use git2::{
+    AnnotatedCommit, Commit, Cred, CredentialType, Delta, Diff, DiffFindOptions, DiffFormat,
+    DiffOptions, ErrorCode, FetchOptions, IndexAddOption, IndexEntry, Oid, Patch, PushOptions,
+    Remote, RemoteCallbacks, Repository, RepositoryInitOptions, RepositoryState, ResetType,
+    Signature, Sort, build::CheckoutBuilder,
+};
+use tracing::debug;
+
+use crate::actions::{
+    folder::sync::git::{Conflict, GitError, GitRepository, PullStatus, TransferProgress},
+    note::history::Revision,
+};
+
+/// The only remote nb-rs works with, set up by `nb-rs sync setup`.
+const REMOTE: &str = "origin";
+
+/// Private keys that are tried after the ssh agent, relative to the home directory.
+const SSH_KEYS: [&str; 2] = [".ssh/id_ed25519", ".ssh/id_rsa"];
+
+type Error = GitError;
+
+/// Where the local branch stands after fetching.
+enum Upstream<'a> {
+    /// Nothing left to do, the branch was up to date or has been fast-forwarded
+    Done(PullStatus),
+    /// Both sides have new commits
+    Diverged(AnnotatedCommit<'a>),
+}
+
+/// [`GitRepository`] backed by libgit2, so syncing works without a git binary.
+pub struct LibGit {
+    repo: Repository,
+}
+
+impl LibGit {
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        Ok(Self {
+            repo: Repository::open(path)?,
+        })
+    }
+
+    /// Creates a repository at `path` whose first branch is `branch`.
+    pub fn init(path: &Path, branch: &str) -> Result<Self, Error> {
+        let repo = Repository::init_opts(path, RepositoryInitOptions::new().initial_head(branch))?;
+
+        Ok(Self { repo })
+    }
+
+    fn workdir(&self) -> &Path {
+        self.repo.workdir().unwrap_or(self.repo.path())
+    }
+
+    /// The branch HEAD points to, even if it has no commits yet.
+    fn current_branch(&self) -> Result<String, Error> {
+        let head = self.repo.find_reference("HEAD")?;
+
+        head.symbolic_target()
+            .and_then(|target| target.strip_prefix("refs/heads/"))
+            .map(|branch| branch.to_string())
+            .ok_or(Error::DetachedHead)
+    }
+
+    fn head_commit(&self) -> Result<Option<Commit<'_>>, Error> {
+        match self.repo.head() {
+            Ok(head) => Ok(Some(head.peel_to_commit()?)),
+            Err(e) if matches!(e.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn find_commit(&self, revision: &str) -> Result<Commit<'_>, Error> {
+        self.repo
+            .revparse_single(revision)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|_| Error::UnknownRevision(revision.to_string()))
+    }
+
+    fn remote(&self) -> Result<Remote<'_>, Error> {
+        self.repo.find_remote(REMOTE).map_err(|e| match e.code() {
+            ErrorCode::NotFound => Error::NoRemote(REMOTE.to_string()),
+            _ => e.into(),
+        })
+    }
+
+    fn signature(&self) -> Result<Signature<'static>, Error> {
+        self.repo.signature().map_err(|_| Error::Identity)
+    }
+
+    /// Authenticates like the git binary would: the ssh agent and the usual keys for ssh
+    /// remotes, the credential helpers for https ones.
+    fn callbacks<'a>(
+        &self,
+        progress: &'a dyn Fn(TransferProgress),
+    ) -> Result<RemoteCallbacks<'a>, Error> {
+        let config = self.repo.config()?;
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let mut attempts = 0;
+
+        let mut callbacks = RemoteCallbacks::new();
+
+        callbacks.credentials(move |url, username, allowed| {
+            attempts += 1;
+            debug!(
+                "Authenticating to {} as {:?} ({:?})",
+                url, username, allowed
+            );
+
+            if allowed.contains(CredentialType::SSH_KEY) {
+                let username = username.unwrap_or("git");
+
+                if attempts == 1 {
+                    return Cred::ssh_key_from_agent(username);
+                }
+
+                let key = home
+                    .as_ref()
+                    .and_then(|home| SSH_KEYS.get(attempts - 2).map(|key| home.join(key)));
+
+                if let Some(key) = key {
+                    return Cred::ssh_key(username, None, &key, None);
+                }
+            } else if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) && attempts == 1 {
+                return Cred::credential_helper(&config, url, username);
+            } else if allowed.contains(CredentialType::DEFAULT) && attempts == 1 {
+                return Cred::default();
+            }
+
+            Err(git2::Error::from_str(&format!(
+                "failed to authenticate to {}",
+                url
+            )))
+        });
+
+        callbacks.transfer_progress(move |stats| {
+            progress(TransferProgress {
+                stage: "Receiving objects",
+                current: stats.received_objects(),
+                total: stats.total_objects(),
+            });
+            true
+        });
+
+        callbacks.push_transfer_progress(move |current, total, _| {
+            progress(TransferProgress {
+                stage: "Writing objects",
+                current,
+                total,
+            });
+        });
+
+        Ok(callbacks)
+    }
+
+    /// Fetches and fast-forwards the local branch if it has no commits of its own.
+    fn fetch_upstream(&self, progress: &dyn Fn(TransferProgress)) -> Result<Upstream<'_>, Error> {
+        self.fetch(progress)?;
+
+        let branch = self.current_branch()?;
+        let upstream = match self
+            .repo
+            .find_reference(&format!("refs/remotes/{}/{}", REMOTE, branch))
+        {
+            Ok(value) => value,
+            Err(e) if e.code() == ErrorCode::NotFound => {
+                return Ok(Upstream::Done(PullStatus::UpToDate));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let theirs = self.repo.reference_to_annotated_commit(&upstream)?;
+        let (analysis, _) = self.repo.merge_analysis(&[&theirs])?;
+
+        if analysis.is_up_to_date() {
+            return Ok(Upstream::Done(PullStatus::UpToDate));
+        }
+
+        if analysis.is_fast_forward() || analysis.is_unborn() {
+            let target = self.repo.find_object(theirs.id(), None)?;
+            self.repo
+                .checkout_tree(&target, Some(CheckoutBuilder::new().safe()))?;
+            self.repo.reference(
+                &format!("refs/heads/{}", branch),
+                theirs.id(),
+                true,
+                "nb-rs: fast-forward",
+            )?;
+
+            return Ok(Upstream::Done(PullStatus::FastForward));
+        }
+
+        Ok(Upstream::Diverged(theirs))
+    }
+
+    /// The commits being merged into HEAD while a merge is in progress.
+    fn merge_heads(&self) -> Result<Vec<Oid>, Error> {
+        if self.repo.state() != RepositoryState::Merge {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(self.repo.path().join("MERGE_HEAD"))?;
+
+        Ok(content
+            .lines()
+            .filter_map(|line| Oid::from_str(line.trim()).ok())
+            .collect())
+    }
+
+    fn index_paths(&self) -> Result<Vec<String>, Error> {
+        Ok(self
+            .repo
+            .index()?
+            .iter()
+            .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            .collect())
+    }
+
+    fn diff_options(paths: &[&str]) -> DiffOptions {
+        let mut options = DiffOptions::new();
+        for path in paths {
+            options.pathspec(path);
+        }
+        options.disable_pathspec_match(true);
+        options
+    }
+
+    /// Formats `diff` between two versions of a file that might have been moved.
+    fn format_renamed(mut diff: Diff) -> Result<String, Error> {
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+        format_patch(&diff)
+    }
+
+    /// The id of the file at `path` in `commit`, if it's there.
+    fn blob_id(commit: &Commit, path: &str) -> Option<Oid> {
+        commit
+            .tree()
+            .ok()?
+            .get_path(Path::new(path))
+            .ok()
+            .map(|entry| entry.id())
+    }
+
+    /// The `Revision` for a commit that changed the file in delta `index` of `diff`.
+    fn revision(commit: &Commit, diff: &Diff, index: usize, path: &str) -> Result<Revision, Error> {
+        let patch = Patch::from_diff(diff, index)?;
+        let (added, removed) = match patch {
+            Some(patch) if !patch.delta().flags().is_binary() => {
+                let (_, added, removed) = patch.line_stats()?;
+                (Some(added), Some(removed))
+            }
+            _ => (None, None),
+        };
+
+        let author = commit.author();
+        let when = author.when();
+        let date = FixedOffset::east_opt(when.offset_minutes() * 60)
+            .zip(DateTime::from_timestamp(when.seconds(), 0))
+            .map(|(offset, date)| date.with_timezone(&offset).to_rfc3339())
+            .unwrap_or_default();
+
+        Ok(Revision {
+            hash: commit.id().to_string(),
+            short_hash: commit
+                .as_object()
+                .short_id()?
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            path: path.to_string(),
+            date,
+            author: author.name().unwrap_or_default().to_string(),
+            message: commit.summary().unwrap_or_default().to_string(),
+            added,
+            removed,
+        })
+    }
+}
+
+/// Collects the files below `directory`, leaving out nested repositories.
+fn files_below(directory: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            if !path.join(".git").exists() {
+                files_below(&path, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Renders `diff` like `git diff`, coloured when colours are enabled.
+fn format_patch(diff: &Diff) -> Result<String, Error> {
+    let mut patch = String::new();
+
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        let content = String::from_utf8_lossy(line.content());
+
+        for text in content.lines() {
+            let text = match line.origin() {
+                '+' => format!("+{}", text).green().to_string(),
+                '-' => format!("-{}", text).red().to_string(),
+                ' ' => format!(" {}", text),
+                'H' => text.cyan().to_string(),
+                'F' => text.bold().to_string(),
+                _ => text.to_string(),
+            };
+
+            patch.push_str(&text);
+            patch.push('\n');
+        }
+
+        true
+    })?;
+
+    Ok(patch)
+}
+
+impl GitRepository for LibGit {
+    fn add_remote(&self, url: &str) -> Result<(), Error> {
+        self.repo.remote(REMOTE, url)?;
+        Ok(())
+    }
+
+    fn track_branch(&self, branch: &str) -> Result<(), Error> {
+        let local = format!("refs/heads/{}", branch);
+
+        let mut config = self.repo.config()?;
+        config.set_str(&format!("branch.{}.remote", branch), REMOTE)?;
+        config.set_str(&format!("branch.{}.merge", branch), &local)?;
+
+        match self
+            .repo
+            .find_reference(&format!("refs/remotes/{}/{}", REMOTE, branch))
+        {
+            Ok(remote) => {
+                let commit = remote.peel_to_commit()?;
+                self.repo
+                    .checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))?;
+                self.repo
+                    .reference(&local, commit.id(), true, "nb-rs: track remote branch")?;
+            }
+            Err(e) if e.code() == ErrorCode::NotFound => {
+                debug!("The remote has no branch '{}' yet", branch);
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        self.repo.set_head(&local)?;
+
+        Ok(())
+    }
+
+    fn register_merge_driver(&self, name: &str, driver: &str, pattern: &str) -> Result<(), Error> {
+        let mut config = self.repo.config()?;
+        config.set_str(&format!("merge.{}.name", name), "nb-rs markdown merge")?;
+        config.set_str(&format!("merge.{}.driver", name), driver)?;
+
+        let attributes = self.repo.path().join("info").join("attributes");
+        let line = format!("{} merge={}", pattern, name);
+        let mut content = fs::read_to_string(&attributes).unwrap_or_default();
+
+        if !content.lines().any(|existing| existing.trim() == line) {
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str(&line);
+            content.push('\n');
+
+            fs::create_dir_all(attributes.parent().unwrap())?;
+            fs::write(&attributes, content)?;
+        }
+
+        Ok(())
+    }
+
+    fn is_tracked(&self, path: &str) -> Result<bool, Error> {
+        let prefix = format!("{}/", path);
+
+        Ok(self
+            .index_paths()?
+            .iter()
+            .any(|tracked| tracked == path || tracked.starts_with(&prefix)))
+    }
+
+    fn stage(&self, paths: &[String]) -> Result<(), Error> {
+        let mut index = self.repo.index()?;
+        let tracked = self.index_paths()?;
+
+        for path in paths {
+            let full = self.workdir().join(path);
+
+            if full.is_dir() {
+                let mut files = Vec::new();
+                files_below(&full, &mut files)?;
+
+                for file in files {
+                    let relative = file.strip_prefix(self.workdir()).unwrap_or(&file);
+
+                    if !self.repo.is_path_ignored(relative)? {
+                        index.add_path(relative)?;
+                    }
+                }
+            } else if full.is_file() {
+                index.add_path(Path::new(path))?;
+            }
+
+            let prefix = format!("{}/", path);
+            for gone in tracked.iter().filter(|tracked| {
+                (*tracked == path || tracked.starts_with(&prefix))
+                    && !self.workdir().join(tracked).exists()
+            }) {
+                index.remove_path(Path::new(gone))?;
+            }
+        }
+
+        index.write()?;
+
+        Ok(())
+    }
+
+    fn stage_all(&self) -> Result<(), Error> {
+        let mut index = self.repo.index()?;
+
+        index.add_all(["*"], IndexAddOption::DEFAULT, None)?;
+        index.update_all(["*"], None)?;
+        index.write()?;
+
+        Ok(())
+    }
+
+    fn commit(&self, message: &str) -> Result<bool, Error> {
+        let mut index = self.repo.index()?;
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+        let head = self.head_commit()?;
+
+        let merge_heads = self.merge_heads()?;
+
+        let unchanged = match &head {
+            None => tree.is_empty(),
+            Some(head) => head.tree_id() == tree.id(),
+        };
+
+        if unchanged && merge_heads.is_empty() {
+            debug!("Nothing to commit");
+            return Ok(false);
+        }
+
+        let mut parents: Vec<Commit> = head.into_iter().collect();
+        for id in merge_heads {
+            parents.push(self.repo.find_commit(id)?);
+        }
+
+        let signature = self.signature()?;
+        self.repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents.iter().collect::<Vec<_>>(),
+        )?;
+
+        self.repo.cleanup_state()?;
+
+        Ok(true)
+    }
+
+    fn fetch(&self, progress: &dyn Fn(TransferProgress)) -> Result<(), Error> {
+        let mut options = FetchOptions::new();
+        options.remote_callbacks(self.callbacks(progress)?);
+
+        self.remote()?
+            .fetch::<&str>(&[], Some(&mut options), None)?;
+
+        Ok(())
+    }
+
+    fn pull(&self, progress: &dyn Fn(TransferProgress)) -> Result<PullStatus, Error> {
+        let theirs = match self.fetch_upstream(progress)? {
+            Upstream::Done(status) => return Ok(status),
+            Upstream::Diverged(theirs) => theirs,
+        };
+
+        self.repo.merge(
+            &[&theirs],
+            None,
+            Some(CheckoutBuilder::new().safe().allow_conflicts(true)),
+        )?;
+
+        if self.repo.index()?.has_conflicts() {
+            let paths = self
+                .conflicts()?
+                .into_iter()
+                .map(|conflict| conflict.path)
+                .collect();
+
+            return Err(Error::Conflicts(paths));
+        }
+
+        self.commit(&format!(
+            "Merge remote-tracking branch '{}/{}'",
+            REMOTE,
+            self.current_branch()?
+        ))?;
+
+        Ok(PullStatus::Merged)
+    }
+
+    fn fast_forward(&self, progress: &dyn Fn(TransferProgress)) -> Result<PullStatus, Error> {
+        match self.fetch_upstream(progress)? {
+            Upstream::Done(status) => Ok(status),
+            Upstream::Diverged(_) => Err(Error::Diverged),
+        }
+    }
+
+    fn pull_rebase(&self, progress: &dyn Fn(TransferProgress)) -> Result<PullStatus, Error> {
+        let theirs = match self.fetch_upstream(progress)? {
+            Upstream::Done(status) => return Ok(status),
+            Upstream::Diverged(theirs) => theirs,
+        };
+
+        let signature = self.signature()?;
+        let mut rebase = self.repo.rebase(None, Some(&theirs), None, None)?;
+
+        while let Some(operation) = rebase.next() {
+            if let Err(e) = operation {
+                rebase.abort()?;
+                return Err(e.into());
+            }
+
+            if self.repo.index()?.has_conflicts() {
+                debug!("Rebasing conflicts, undoing it");
+                rebase.abort()?;
+                return Err(Error::RebaseConflict);
+            }
+
+            // commits the remote already has are skipped
+            if let Err(e) = rebase.commit(None, &signature, None)
+                && e.code() != ErrorCode::Applied
+            {
+                rebase.abort()?;
+                return Err(e.into());
+            }
+        }
+
+        rebase.finish(Some(&signature))?;
+
+        Ok(PullStatus::Rebased)
+    }
+
+    fn conflicts(&self) -> Result<Vec<Conflict>, Error> {
+        let content = |entry: &Option<IndexEntry>| -> Result<Option<Vec<u8>>, Error> {
+            match entry {
+                None => Ok(None),
+                Some(entry) => Ok(Some(self.repo.find_blob(entry.id)?.content().to_vec())),
+            }
+        };
+
+        let mut conflicts = Vec::new();
+
+        for conflict in self.repo.index()?.conflicts()? {
+            let conflict = conflict?;
+            let path = [&conflict.our, &conflict.their, &conflict.ancestor]
+                .into_iter()
+                .find_map(|entry| entry.as_ref())
+                .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+                .unwrap_or_default();
+
+            conflicts.push(Conflict {
+                path,
+                ancestor: content(&conflict.ancestor)?,
+                ours: content(&conflict.our)?,
+                theirs: content(&conflict.their)?,
+            });
+        }
+
+        Ok(conflicts)
+    }
+
+    fn abort_merge(&self) -> Result<(), Error> {
+        if let Some(head) = self.head_commit()? {
+            self.repo.reset(head.as_object(), ResetType::Hard, None)?;
+        }
+
+        self.repo.cleanup_state()?;
+
+        Ok(())
+    }
+
+    fn push(&self, progress: &dyn Fn(TransferProgress)) -> Result<(), Error> {
+        if self.head_commit()?.is_none() {
+            debug!("Nothing to push yet");
+            return Ok(());
+        }
+
+        let reference = format!("refs/heads/{}", self.current_branch()?);
+        let rejected = RefCell::new(None);
+
+        let mut callbacks = self.callbacks(progress)?;
+        callbacks.push_update_reference(|reference, status| {
+            if let Some(reason) = status {
+                // servers word it differently, e.g. "non-fast-forward" or "fetch first"
+                let error = match reason.contains("fast-forward") || reason.contains("fetch first")
+                {
+                    true => Error::NotFastForward,
+                    false => Error::PushRejected {
+                        reference: reference.to_string(),
+                        reason: reason.to_string(),
+                    },
+                };
+
+                *rejected.borrow_mut() = Some(error);
+            }
+            Ok(())
+        });
+
+        let mut options = PushOptions::new();
+        options.remote_callbacks(callbacks);
+
+        self.remote()?
+            .push(
+                &[format!("{}:{}", reference, reference)],
+                Some(&mut options),
+            )
+            .map_err(|e| match e.code() {
+                ErrorCode::NotFastForward => Error::NotFastForward,
+                _ => e.into(),
+            })?;
+
+        drop(options);
+
+        match rejected.into_inner() {
+            None => Ok(()),
+            Some(e) => Err(e),
+        }
+    }
+
+    fn log(&self, path: &str) -> Result<Vec<Revision>, Error> {
+        if self.head_commit()?.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+
+        let mut current = path.to_string();
+        let mut revisions = Vec::new();
+
+        for id in revwalk {
+            let commit = self.repo.find_commit(id?)?;
+            let blob = Self::blob_id(&commit, &current);
+            let parents: Vec<Commit> = commit.parents().collect();
+            let parent_blobs: Vec<Option<Oid>> = parents
+                .iter()
+                .map(|parent| Self::blob_id(parent, &current))
+                .collect();
+
+            // merges only count if they changed the note compared to every side, like when
+            // resolving a conflict
+            let changed = match parent_blobs.is_empty() {
+                true => blob.is_some(),
+                false => parent_blobs.iter().all(|parent| *parent != blob),
+            };
+
+            if !changed {
+                continue;
+            }
+
+            let tree = commit.tree()?;
+            let parent_tree = parents.first().map(|parent| parent.tree()).transpose()?;
+
+            let mut options = Self::diff_options(&[&current]);
+            let mut diff = self.repo.diff_tree_to_tree(
+                parent_tree.as_ref(),
+                Some(&tree),
+                Some(&mut options),
+            )?;
+            let mut index = 0;
+            let mut renamed_from = None;
+
+            // the note appeared in this commit, it might have been moved here
+            if blob.is_some() && parent_blobs.first().is_some_and(|parent| parent.is_none()) {
+                let mut full =
+                    self.repo
+                        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+                full.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+                let rename = full.deltas().position(|delta| {
+                    delta.status() == Delta::Renamed
+                        && delta.new_file().path() == Some(Path::new(&current))
+                });
+
+                if let Some(position) = rename {
+                    renamed_from = full
+                        .get_delta(position)
+                        .and_then(|delta| delta.old_file().path().map(|path| path.to_path_buf()));
+                    diff = full;
+                    index = position;
+                }
+            }
+
+            revisions.push(Self::revision(&commit, &diff, index, &current)?);
+
+            if let Some(from) = renamed_from {
+                debug!("'{}' was renamed from {:?}", current, from);
+                current = from.to_str().unwrap_or_default().to_string();
+            }
+        }
+
+        Ok(revisions)
+    }
+
+    fn path_at(&self, path: &str, revision: &str) -> Result<String, Error> {
+        let target = self.find_commit(revision)?.id();
+
+        // the newest change at or before `revision` knows where the file was
+        for change in self.log(path)? {
+            let id = Oid::from_str(&change.hash)?;
+            if id == target || self.repo.graph_descendant_of(target, id)? {
+                return Ok(change.path);
+            }
+        }
+
+        Ok(path.to_string())
+    }
+
+    fn file_at(&self, revision: &str, path: &str) -> Result<Vec<u8>, Error> {
+        let commit = self.find_commit(revision)?;
+        let entry = commit
+            .tree()?
+            .get_path(Path::new(path))
+            .map_err(|_| Error::NotInRevision {
+                path: path.to_string(),
+                revision: revision.to_string(),
+            })?;
+
+        Ok(self.repo.find_blob(entry.id())?.content().to_vec())
+    }
+
+    fn diff(&self, from: &str, to: Option<&str>, path: &str) -> Result<String, Error> {
+        let from_path = self.path_at(path, from)?;
+        let to_path = match to {
+            Some(to) => self.path_at(path, to)?,
+            None => path.to_string(),
+        };
+
+        let from = self.find_commit(from)?.tree()?;
+        let mut options = Self::diff_options(&[&from_path, &to_path]);
+
+        let diff = match to {
+            None => self
+                .repo
+                .diff_tree_to_workdir_with_index(Some(&from), Some(&mut options))?,
+            Some(to) => {
+                let to = self.find_commit(to)?.tree()?;
+                self.repo
+                    .diff_tree_to_tree(Some(&from), Some(&to), Some(&mut options))?
+            }
+        };
+
+        Self::format_renamed(diff)
+    }
+
+    fn diff_commit(&self, revision: &str, path: &str) -> Result<String, Error> {
+        let commit = self.find_commit(revision)?;
+        let parent = commit.parents().next();
+
+        let mut paths = vec![self.path_at(path, revision)?];
+        if let Some(parent) = &parent {
+            paths.push(self.path_at(path, &parent.id().to_string())?);
+        }
+        let paths: Vec<&str> = paths.iter().map(|path| path.as_str()).collect();
+
+        let parent = parent.map(|parent| parent.tree()).transpose()?;
+        let mut options = Self::diff_options(&paths);
+
+        let diff = self.repo.diff_tree_to_tree(
+            parent.as_ref(),
+            Some(&commit.tree()?),
+            Some(&mut options),
+        )?;
+
+        Self::format_renamed(diff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn no_progress(_: TransferProgress) {}
+
+    /// A clone of the bare repository `remote`, set up like `nb-rs sync setup` does.
+    fn clone(directory: &Path, remote: &Path, name: &str) -> LibGit {
+        let repository = LibGit::init(directory, "main").unwrap();
+
+        let mut config = repository.repo.config().unwrap();
+        config.set_str("user.name", name).unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+
+        repository.add_remote(remote.to_str().unwrap()).unwrap();
+        repository.fetch(&no_progress).unwrap();
+        repository.track_branch("main").unwrap();
+
+        repository
+    }
+
+    fn write_and_commit(repository: &LibGit, path: &str, content: &str) {
+        let full = repository.workdir().join(path);
+        fs::create_dir_all(full.parent().unwrap()).unwrap();
+        fs::write(full, content).unwrap();
+
+        repository.stage(&[path.to_string()]).unwrap();
+        assert!(repository.commit(&format!("Edit: {}", path)).unwrap());
+    }
+
+    fn setup() -> (TempDir, LibGit, LibGit) {
+        let directory = TempDir::new().unwrap();
+        let remote = directory.path().join("remote.git");
+        Repository::init_bare(&remote).unwrap();
+
+        let alice = clone(&directory.path().join("alice"), &remote, "Alice");
+        let bob = clone(&directory.path().join("bob"), &remote, "Bob");
+
+        (directory, alice, bob)
+    }
+
+    #[test]
+    fn commit_without_changes_does_nothing() {
+        let (_directory, alice, _) = setup();
+
+        write_and_commit(&alice, "todo.md", "- [ ] milk\n");
+
+        alice.stage(&["todo.md".to_string()]).unwrap();
+        assert!(!alice.commit("Edit: todo.md").unwrap());
+    }
+
+    #[test]
+    fn push_and_fast_forward() {
+        let (_directory, alice, bob) = setup();
+
+        write_and_commit(&alice, "todo.md", "- [ ] milk\n");
+        alice.push(&no_progress).unwrap();
+
+        assert_eq!(bob.pull(&no_progress).unwrap(), PullStatus::FastForward);
+        assert_eq!(
+            fs::read_to_string(bob.workdir().join("todo.md")).unwrap(),
+            "- [ ] milk\n"
+        );
+        assert_eq!(bob.pull(&no_progress).unwrap(), PullStatus::UpToDate);
+    }
+
+    #[test]
+    fn diverged_push_is_rejected_until_merged() {
+        let (_directory, alice, bob) = setup();
+
+        write_and_commit(&alice, "a.md", "alice\n");
+        alice.push(&no_progress).unwrap();
+
+        write_and_commit(&bob, "b.md", "bob\n");
+        assert!(matches!(
+            bob.push(&no_progress),
+            Err(GitError::NotFastForward)
+        ));
+
+        assert_eq!(bob.pull(&no_progress).unwrap(), PullStatus::Merged);
+        assert!(bob.workdir().join("a.md").exists());
+        bob.push(&no_progress).unwrap();
+    }
+
+    #[test]
+    fn fast_forward_stops_when_diverged() {
+        let (_directory, alice, bob) = setup();
+
+        write_and_commit(&alice, "a.md", "alice\n");
+        alice.push(&no_progress).unwrap();
+        assert_eq!(
+            bob.fast_forward(&no_progress).unwrap(),
+            PullStatus::FastForward
+        );
+
+        write_and_commit(&alice, "a.md", "alice again\n");
+        alice.push(&no_progress).unwrap();
+        write_and_commit(&bob, "b.md", "bob\n");
+
+        assert!(matches!(
+            bob.fast_forward(&no_progress),
+            Err(GitError::Diverged)
+        ));
+        assert_eq!(
+            fs::read_to_string(bob.workdir().join("a.md")).unwrap(),
+            "alice\n"
+        );
+    }
+
+    #[test]
+    fn conflicting_edits_are_reported() {
+        let (_directory, alice, bob) = setup();
+
+        write_and_commit(&alice, "meeting.md", "start\n");
+        alice.push(&no_progress).unwrap();
+        bob.pull(&no_progress).unwrap();
+
+        write_and_commit(&alice, "meeting.md", "alice\n");
+        alice.push(&no_progress).unwrap();
+        write_and_commit(&bob, "meeting.md", "bob\n");
+
+        match bob.pull(&no_progress) {
+            Err(GitError::Conflicts(paths)) => assert_eq!(paths, vec!["meeting.md"]),
+            other => panic!("expected a conflict, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn pull_rebase_keeps_history_linear() {
+        let (_directory, alice, bob) = setup();
+
+        write_and_commit(&alice, "a.md", "alice\n");
+        alice.push(&no_progress).unwrap();
+        write_and_commit(&bob, "b.md", "bob\n");
+
+        assert_eq!(bob.pull_rebase(&no_progress).unwrap(), PullStatus::Rebased);
+
+        let head = bob.head_commit().unwrap().unwrap();
+        assert_eq!(head.parent_count(), 1);
+        assert_eq!(head.summary(), Some("Edit: b.md"));
+        bob.push(&no_progress).unwrap();
+    }
+
+    #[test]
+    fn conflicting_rebase_is_undone() {
+        let (_directory, alice, bob) = setup();
+
+        write_and_commit(&alice, "meeting.md", "start\n");
+        alice.push(&no_progress).unwrap();
+        bob.pull(&no_progress).unwrap();
+
+        write_and_commit(&alice, "meeting.md", "alice\n");
+        alice.push(&no_progress).unwrap();
+        write_and_commit(&bob, "meeting.md", "bob\n");
+
+        assert!(matches!(
+            bob.pull_rebase(&no_progress),
+            Err(GitError::RebaseConflict)
+        ));
+        assert_eq!(bob.repo.state(), RepositoryState::Clean);
+        assert_eq!(
+            fs::read_to_string(bob.workdir().join("meeting.md")).unwrap(),
+            "bob\n"
+        );
+    }
+
+    #[test]
+    fn aborted_merge_goes_back_to_head() {
+        let (_directory, alice, bob) = setup();
+
+        write_and_commit(&alice, "meeting.md", "start\n");
+        alice.push(&no_progress).unwrap();
+        bob.pull(&no_progress).unwrap();
+
+        write_and_commit(&alice, "meeting.md", "alice\n");
+        alice.push(&no_progress).unwrap();
+        write_and_commit(&bob, "meeting.md", "bob\n");
+
+        assert!(bob.pull(&no_progress).is_err());
+
+        let conflicts = bob.conflicts().unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].ancestor.as_deref(), Some(&b"start\n"[..]));
+        assert_eq!(conflicts[0].ours.as_deref(), Some(&b"bob\n"[..]));
+        assert_eq!(conflicts[0].theirs.as_deref(), Some(&b"alice\n"[..]));
+
+        bob.abort_merge().unwrap();
+
+        assert!(bob.conflicts().unwrap().is_empty());
+        assert_eq!(bob.repo.state(), RepositoryState::Clean);
+        assert_eq!(
+            fs::read_to_string(bob.workdir().join("meeting.md")).unwrap(),
+            "bob\n"
+        );
+    }
+
+    #[test]
+    fn stage_records_removed_folders() {
+        let (_directory, alice, _) = setup();
+
+        write_and_commit(&alice, "work/todo.md", "- [ ] milk\n");
+        assert!(alice.is_tracked("work").unwrap());
+
+        fs::remove_dir_all(alice.workdir().join("work")).unwrap();
+        alice.stage(&["work".to_string()]).unwrap();
+        assert!(alice.commit("Remove folder: work").unwrap());
+
+        assert!(!alice.is_tracked("work").unwrap());
+    }
+
+    #[test]
+    fn log_follows_renames() {
+        let (_directory, alice, _) = setup();
+
+        write_and_commit(&alice, "old.md", "one\ntwo\n");
+
+        fs::rename(
+            alice.workdir().join("old.md"),
+            alice.workdir().join("new.md"),
+        )
+        .unwrap();
+        alice
+            .stage(&["old.md".to_string(), "new.md".to_string()])
+            .unwrap();
+        alice.commit("Move: old.md -> new.md").unwrap();
+
+        write_and_commit(&alice, "new.md", "one\ntwo\nthree\n");
+
+        let revisions = alice.log("new.md").unwrap();
+        let messages: Vec<&str> = revisions.iter().map(|rev| rev.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["Edit: new.md", "Move: old.md -> new.md", "Edit: old.md"]
+        );
+        assert_eq!(revisions[0].added, Some(1));
+        assert_eq!(revisions[2].added, Some(2));
+
+        let paths: Vec<&str> = revisions.iter().map(|rev| rev.path.as_str()).collect();
+        assert_eq!(paths, vec!["new.md", "new.md", "old.md"]);
+
+        let first = &revisions[2].hash;
+        assert_eq!(alice.path_at("new.md", first).unwrap(), "old.md");
+        assert_eq!(alice.file_at(first, "old.md").unwrap(), b"one\ntwo\n");
+        assert!(matches!(
+            alice.file_at("nope", "old.md"),
+            Err(GitError::UnknownRevision(_))
+        ));
+
+        // across the move, the diff is the added line and not the whole note
+        let diff = alice.diff(first, None, "new.md").unwrap();
+        assert!(diff.contains("rename from old.md"), "{}", diff);
+        assert!(diff.contains("+three"), "{}", diff);
+        assert!(!diff.contains("+one"), "{}", diff);
+
+        let moved = alice.diff_commit(&revisions[1].hash, "new.md").unwrap();
+        assert!(moved.contains("rename to new.md"), "{}", moved);
+        assert!(!moved.contains("+one"), "{}", moved);
+    }
+}
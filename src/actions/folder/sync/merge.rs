@@ -0,0 +1,406 @@
+use crate::actions::{
+    folder::sync::git::{GitError, GitRepository},
+    note::tasks::parse_checkbox,
+};
+
+/// The extension of the notes that are merged block by block.
+pub const MARKDOWN_EXTENSION: &str = "md";
+
+/// Name of the git merge driver in `.git/config` and `.git/info/attributes`.
+pub const MERGE_DRIVER: &str = "nb-rs";
+
+const LOCAL_MARKER: &str = "<<<<<<< local\n";
+const SEPARATOR_MARKER: &str = "=======\n";
+const REMOTE_MARKER: &str = ">>>>>>> remote\n";
+
+/// Registers `nb-rs merge-driver` for the markdown notes of `repository`, so that merges done
+/// with the git command line get the same block merge as `nb-rs sync`.
+pub fn register_merge_driver(repository: &dyn GitRepository) -> Result<(), GitError> {
+    let executable = std::env::current_exe()?;
+    let driver = format!(
+        "'{}' merge-driver %O %A %B %P",
+        executable.to_string_lossy()
+    );
+
+    repository.register_merge_driver(MERGE_DRIVER, &driver, &format!("*.{}", MARKDOWN_EXTENSION))
+}
+
+/// The result of a three-way merge of a note.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedNote {
+    pub content: String,
+    /// How many places were changed on both sides and got conflict markers
+    pub conflicts: usize,
+}
+
+/// Merges two versions of a markdown note that both started from `ancestor`.
+///
+/// The notes are compared block by block: list items, paragraphs, headings, table rows, code
+/// blocks and the front matter. Blocks added at the same place on both sides are all kept, so two
+/// people appending to one list merge cleanly, and a checkbox checked on one side survives a text
+/// change on the other. Only a block that was changed differently on both sides gets conflict
+/// markers. Without an ancestor, notes that don't start the same way are unrelated and conflict
+/// as a whole.
+pub fn merge_notes(ancestor: &str, ours: &str, theirs: &str) -> MergedNote {
+    let ancestor = split_blocks(ancestor);
+    let local = split_blocks(ours);
+    let remote = split_blocks(theirs);
+
+    if ancestor.is_empty() && !local.is_empty() && !remote.is_empty() && local[0] != remote[0] {
+        let mut merged = MergedNote {
+            content: String::new(),
+            conflicts: 0,
+        };
+        merged.push_conflict(&local, &remote);
+        return merged;
+    }
+
+    let local_matches = matching_blocks(&ancestor, &local);
+    let remote_matches = matching_blocks(&ancestor, &remote);
+
+    let mut merged = MergedNote {
+        content: String::new(),
+        conflicts: 0,
+    };
+
+    let (mut base, mut a, mut b) = (0, 0, 0);
+    loop {
+        // the next block that is still there on both sides
+        let next = (base..ancestor.len())
+            .find_map(|i| Some((i, local_matches[i]?, remote_matches[i]?)))
+            .unwrap_or((ancestor.len(), local.len(), remote.len()));
+
+        if next != (base, a, b) {
+            merged.push_chunk(
+                &ancestor[base..next.0],
+                &local[a..next.1],
+                &remote[b..next.2],
+            );
+        }
+
+        if next.0 == ancestor.len() {
+            break;
+        }
+
+        merged.content.push_str(&ancestor[next.0]);
+        (base, a, b) = (next.0 + 1, next.1 + 1, next.2 + 1);
+    }
+
+    // an editor that leaves out the last newline shouldn't cause a change on every merge
+    if !ours.is_empty() && !ours.ends_with('\n') && merged.content.ends_with('\n') {
+        merged.content.pop();
+    }
+
+    merged
+}
+
+impl MergedNote {
+    /// Adds the part of the note where at least one side changed something.
+    fn push_chunk(&mut self, ancestor: &[String], ours: &[String], theirs: &[String]) {
+        if ours == ancestor || ours == theirs {
+            self.push_blocks(theirs);
+        } else if theirs == ancestor {
+            self.push_blocks(ours);
+        } else if ours.len() >= ancestor.len() && theirs.len() >= ancestor.len() {
+            // the changed blocks first, then what was added after them on either side
+            let added = ancestor.len();
+            for ((ancestor, ours), theirs) in ancestor.iter().zip(ours).zip(theirs) {
+                match merge_block(ancestor, ours, theirs) {
+                    Some(block) => self.content.push_str(&block),
+                    None => {
+                        self.push_conflict(std::slice::from_ref(ours), std::slice::from_ref(theirs))
+                    }
+                }
+            }
+
+            // keep ours first and what only they added
+            self.push_blocks(&ours[added..]);
+            for block in &theirs[added..] {
+                if block.trim().is_empty() || !ours[added..].contains(block) {
+                    self.content.push_str(block);
+                }
+            }
+        } else {
+            self.push_conflict(ours, theirs);
+        }
+    }
+
+    fn push_blocks(&mut self, blocks: &[String]) {
+        for block in blocks {
+            self.content.push_str(block);
+        }
+    }
+
+    fn push_conflict(&mut self, ours: &[String], theirs: &[String]) {
+        self.conflicts += 1;
+
+        self.content.push_str(LOCAL_MARKER);
+        self.push_blocks(ours);
+        self.content.push_str(SEPARATOR_MARKER);
+        self.push_blocks(theirs);
+        self.content.push_str(REMOTE_MARKER);
+    }
+}
+
+/// Merges one block that is at the same place on all sides, or `None` if both changed it.
+fn merge_block(ancestor: &str, ours: &str, theirs: &str) -> Option<String> {
+    if ours == ancestor || ours == theirs {
+        return Some(theirs.to_string());
+    }
+    if theirs == ancestor {
+        return Some(ours.to_string());
+    }
+
+    // one side only checked or unchecked the box, the other changed the text
+    let state = |block: &str| parse_checkbox(block.lines().next()?).map(|(_, done, _)| done);
+    let (ancestor_done, ours_done, theirs_done) = (state(ancestor)?, state(ours)?, state(theirs)?);
+    let done = match ours_done != ancestor_done {
+        true => ours_done,
+        false => theirs_done,
+    };
+
+    let unchecked = |block: &str| with_checkbox(block, false);
+    if unchecked(ours) == unchecked(ancestor) {
+        Some(with_checkbox(theirs, done))
+    } else if unchecked(theirs) == unchecked(ancestor) {
+        Some(with_checkbox(ours, done))
+    } else {
+        None
+    }
+}
+
+/// The block with the checkbox of its first line set to `done`.
+fn with_checkbox(block: &str, done: bool) -> String {
+    let prefix = match block.lines().next().and_then(parse_checkbox) {
+        Some((prefix, _, _)) => prefix.len(),
+        None => return block.to_string(),
+    };
+
+    let checkbox = match done {
+        true => "[x]",
+        false => "[ ]",
+    };
+
+    format!("{}{}{}", &block[..prefix], checkbox, &block[prefix + 3..])
+}
+
+/// For every block of `ancestor`, the index of the same block in `other` if it's still there,
+/// following the longest common subsequence of both.
+fn matching_blocks(ancestor: &[String], other: &[String]) -> Vec<Option<usize>> {
+    let (n, m) = (ancestor.len(), other.len());
+
+    // lengths[i][j] is the longest common subsequence of ancestor[i..] and other[j..]
+    let mut lengths = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = match ancestor[i] == other[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+
+    let mut matches = vec![None; n];
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if ancestor[i] == other[j] {
+            matches[i] = Some(j);
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    matches
+}
+
+fn is_list_item(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("- ") || trimmed.starts_with("* ") || trimmed.starts_with("+ ") {
+        return true;
+    }
+
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    let rest = &trimmed[digits..];
+    digits > 0 && (rest.starts_with(". ") || rest.starts_with(") "))
+}
+
+fn is_fence(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    ["```", "~~~"]
+        .into_iter()
+        .find(|fence| trimmed.starts_with(fence))
+}
+
+/// Whether the line starts a block of its own and ends a paragraph.
+fn starts_block(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.trim().is_empty()
+        || trimmed.starts_with('#')
+        || trimmed.starts_with('|')
+        || is_fence(line).is_some()
+        || is_list_item(line)
+}
+
+/// Splits a note into the blocks that are merged as a whole. Every block keeps its line
+/// endings, so joining them gives back the note. A missing newline at the end is added.
+fn split_blocks(content: &str) -> Vec<String> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let mut blocks = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+        let start = i;
+        i += 1;
+
+        if start == 0 && (trimmed == "---" || trimmed == "+++") {
+            // front matter, up to the closing delimiter
+            while i < lines.len() && lines[i].trim() != trimmed {
+                i += 1;
+            }
+            i = (i + 1).min(lines.len());
+        } else if let Some(fence) = is_fence(line) {
+            while i < lines.len() && !lines[i].trim_start().starts_with(fence) {
+                i += 1;
+            }
+            i = (i + 1).min(lines.len());
+        } else if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('|') {
+            // a line of its own
+        } else if is_list_item(line) {
+            // wrapped lines of the item, nested items are blocks of their own
+            while i < lines.len() && !starts_block(lines[i]) && lines[i].starts_with([' ', '\t']) {
+                i += 1;
+            }
+        } else {
+            while i < lines.len() && !starts_block(lines[i]) {
+                i += 1;
+            }
+        }
+
+        let mut block = lines[start..i].concat();
+        if !block.ends_with('\n') {
+            block.push('\n');
+        }
+        blocks.push(block);
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_to_the_same_list_are_kept() {
+        let ancestor = "# Meeting\n\n- agenda\n";
+        let ours = "# Meeting\n\n- agenda\n- budget\n";
+        let theirs = "# Meeting\n\n- agenda\n- hiring\n";
+
+        let merged = merge_notes(ancestor, ours, theirs);
+
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(
+            merged.content,
+            "# Meeting\n\n- agenda\n- budget\n- hiring\n"
+        );
+    }
+
+    #[test]
+    fn changes_to_different_blocks_are_combined() {
+        let ancestor = "Intro\n\n- one\n- two\n\nOutro\n";
+        let ours = "Intro changed\n\n- one\n- two\n\nOutro\n";
+        let theirs = "Intro\n\n- one\n- two\n\nOutro changed\n";
+
+        let merged = merge_notes(ancestor, ours, theirs);
+
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(
+            merged.content,
+            "Intro changed\n\n- one\n- two\n\nOutro changed\n"
+        );
+    }
+
+    #[test]
+    fn checked_box_survives_a_text_change() {
+        let ancestor = "- [ ] renew certs\n- [ ] call bob\n";
+        let ours = "- [x] renew certs\n- [ ] call bob\n";
+        let theirs = "- [ ] renew certs @due(2026-01-31)\n- [ ] call bob\n";
+
+        let merged = merge_notes(ancestor, ours, theirs);
+
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(
+            merged.content,
+            "- [x] renew certs @due(2026-01-31)\n- [ ] call bob\n"
+        );
+    }
+
+    #[test]
+    fn changed_last_item_and_appends_are_combined() {
+        let ancestor = "- agenda\n- [ ] renew certs\n";
+        let ours = "- agenda\n- [x] renew certs\n- budget\n";
+        let theirs = "- agenda\n- [ ] renew certs by friday\n- hiring\n";
+
+        let merged = merge_notes(ancestor, ours, theirs);
+
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(
+            merged.content,
+            "- agenda\n- [x] renew certs by friday\n- budget\n- hiring\n"
+        );
+    }
+
+    #[test]
+    fn unrelated_notes_without_ancestor_conflict() {
+        let merged = merge_notes("", "# [ ] x\n", "# [ ] y\n");
+
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.content,
+            "<<<<<<< local\n# [ ] x\n=======\n# [ ] y\n>>>>>>> remote\n"
+        );
+    }
+
+    #[test]
+    fn same_block_changed_on_both_sides_conflicts() {
+        let ancestor = "- one\n- two\n- three\n";
+        let ours = "- one\n- two, local\n- three\n";
+        let theirs = "- one\n- two, remote\n- three\n";
+
+        let merged = merge_notes(ancestor, ours, theirs);
+
+        assert_eq!(merged.conflicts, 1);
+        assert_eq!(
+            merged.content,
+            "- one\n<<<<<<< local\n- two, local\n=======\n- two, remote\n>>>>>>> remote\n- three\n"
+        );
+    }
+
+    #[test]
+    fn code_blocks_are_merged_as_a_whole() {
+        let blocks = split_blocks("---\ntitle: a\n---\n```\n- not a list\n\n```\ntext\nmore\n");
+
+        assert_eq!(
+            blocks,
+            vec![
+                "---\ntitle: a\n---\n",
+                "```\n- not a list\n\n```\n",
+                "text\nmore\n"
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_final_newline_is_kept() {
+        let merged = merge_notes("- a", "- a\n- b", "- a\n- c\n");
+
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.content, "- a\n- b\n- c");
+    }
+}
@@ -1,3 +1,10 @@
+pub mod conflicts;
+pub mod git;
+pub mod libgit;
+pub mod merge;
+pub mod mutation;
 pub mod setup;
 pub mod sync_manual;
+pub mod sync_move;
 pub mod sync_note;
+pub mod sync_open;
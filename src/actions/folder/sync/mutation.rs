@@ -0,0 +1,113 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+use tracing::debug;
+
+use crate::{
+    actions::folder::{
+        model::Folder,
+        sync::sync_note::{SyncError, SyncStatus},
+    },
+    config::model::Config,
+    output,
+};
+
+/// What a command did to the notebook, used as the `{action}` of the commit message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MutationAction {
+    Create,
+    Edit,
+    Remove,
+    Restore,
+    Move,
+    CreateFolder,
+    RemoveFolder,
+    RestoreFolder,
+    MoveFolder,
+    Template,
+}
+
+impl MutationAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MutationAction::Create => "Create",
+            MutationAction::Edit => "Edit",
+            MutationAction::Remove => "Remove",
+            MutationAction::Restore => "Restore",
+            MutationAction::Move => "Move",
+            MutationAction::CreateFolder => "Create folder",
+            MutationAction::RemoveFolder => "Remove folder",
+            MutationAction::RestoreFolder => "Restore folder",
+            MutationAction::MoveFolder => "Move folder",
+            MutationAction::Template => "Template",
+        }
+    }
+}
+
+/// A change to the notebook that has to end up in git. Every command that changes notes or
+/// folders records one, so that the remote doesn't drift away from the local notebook.
+#[derive(Debug, Clone)]
+pub struct Mutation {
+    pub action: MutationAction,
+    /// What the change is about, e.g. `work/todo.md` or `old.md -> new.md`
+    pub name: String,
+    /// Everything that changed, including paths that don't exist anymore
+    pub paths: Vec<PathBuf>,
+}
+
+impl Mutation {
+    pub fn new(action: MutationAction, name: impl ToString, paths: Vec<PathBuf>) -> Self {
+        Self {
+            action,
+            name: name.to_string(),
+            paths,
+        }
+    }
+
+    /// Commits the changed paths in the git repository they belong to. The commit is pushed
+    /// unless we're offline or `auto_push` is off.
+    pub fn commit(&self, config: &Config) -> Result<SyncStatus, SyncError> {
+        let git_root = match self.paths.first() {
+            None => None,
+            Some(path) => Folder::from_pathbuf(path, ".")?.sync_git_root(config),
+        };
+
+        let git_root = match git_root {
+            None => return Ok(SyncStatus::NotSetup),
+            Some(value) => value,
+        };
+
+        let root = git_root.get_path();
+        let paths: Vec<PathBuf> = self
+            .paths
+            .iter()
+            .filter(|path| path.starts_with(&root) && **path != root)
+            .cloned()
+            .collect();
+
+        debug!("Recording {:?} of '{}'", self.action, self.name);
+
+        let push = !config.offline && config.auto_push;
+
+        match push {
+            true => output::message_inline("Syncing with remote..."),
+            false => output::message_inline("Committing..."),
+        }
+
+        git_root.sync_commit(
+            &paths,
+            &config.commit_message(self.action.label(), &self.name),
+        )?;
+
+        if !push {
+            output::message(&"Done".green().to_string());
+            return Ok(SyncStatus::Skipped);
+        }
+
+        git_root.sync_push(config, git_root.sync_repository()?.as_ref())?;
+
+        output::message(&"Done".green().to_string());
+
+        Ok(SyncStatus::Success)
+    }
+}
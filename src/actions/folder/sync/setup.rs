@@ -1,21 +1,27 @@
-use std::{
-    io::Read,
-    process::{Command, ExitStatus, Stdio},
-};
+use std::fs;
 
-use itertools::Itertools;
 use thiserror::Error;
-use tracing::debug;
 
-use crate::{actions::folder::model::Folder, config::model::Config};
+use crate::{
+    actions::folder::{
+        model::Folder,
+        sync::{
+            git::{GitError, GitRepository},
+            libgit::LibGit,
+            merge::register_merge_driver,
+        },
+    },
+    config::model::Config,
+    output::{self, ProgressLine},
+};
 
 #[derive(Error, Debug)]
 pub enum SetupSyncError {
     #[error("sync is already configured!")]
     SyncExists,
 
-    #[error("failed to run git command '{}':\n{}", .command, .err)]
-    Git { command: String, err: String },
+    #[error(transparent)]
+    Git(#[from] GitError),
 
     #[error(transparent)]
     Io(#[from] std::io::Error),
@@ -25,68 +31,28 @@ type Error = SetupSyncError;
 
 impl Folder {
     pub fn sync_exists(&self, config: &Config) -> bool {
+        self.sync_git_root(config).is_some()
+    }
+
+    /// Returns the closest folder above (or equal to) this one that is a git repository.
+    pub fn sync_git_root(&self, config: &Config) -> Option<Folder> {
         let mut path = self.get_path();
-        while path != config.data_dir {
+        while path != config.data_dir && path.starts_with(&config.data_dir) {
             let mut tmp = path.clone();
             tmp.push(".git");
             if tmp.exists() {
-                return true;
+                return Folder::from_pathbuf(&path, ".").ok();
             }
 
             path.pop();
         }
 
-        false
-    }
-    pub fn sync_run_git_command(&self, args: &[&str]) -> Result<ExitStatus, Error> {
-        self.sync_run_git_command_conf(args, false)
+        None
     }
 
-    pub fn sync_run_git_command_conf(
-        &self,
-        args: &[&str],
-        ignore_errors: bool,
-    ) -> Result<ExitStatus, Error> {
-        let mut command = Command::new("git");
-        command
-            .args(args)
-            .current_dir(self.get_path())
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        let mut child = command.spawn()?;
-        let status = child.wait()?;
-
-        let mut stderr = String::new();
-        child.stderr.unwrap().read_to_string(&mut stderr)?;
-
-        let mut stdout = String::new();
-        child.stdout.unwrap().read_to_string(&mut stdout)?;
-
-        if !status.success() && !ignore_errors {
-            return Err(Error::Git {
-                command: args.join(" "),
-                err: format!("Stdout: {}\n\nStderr: {}", stdout, stderr),
-            });
-        }
-
-        debug!(
-            "Ran 'git {}'\nStdout:{}\nStderr:{}\n",
-            args.iter()
-                .map(|arg| {
-                    if arg.contains(" ") {
-                        format!("\"{}\"", arg)
-                    } else {
-                        arg.to_string()
-                    }
-                })
-                .join(" "),
-            stdout,
-            stderr
-        );
-
-        Ok(status)
+    /// Opens the git repository of this folder. Has to be called on the git root folder.
+    pub fn sync_repository(&self) -> Result<Box<dyn GitRepository>, GitError> {
+        Ok(Box::new(LibGit::open(&self.get_path())?))
     }
 
     pub fn sync_setup(&self, config: &Config, repo: &str, branch: &str) -> Result<(), Error> {
@@ -94,12 +60,23 @@ impl Folder {
             return Err(Error::SyncExists);
         }
 
-        self.sync_run_git_command(&["init", "-b", branch])?;
-        self.sync_run_git_command(&["remote", "add", "origin", repo])?;
-        self.sync_run_git_command(&["fetch", "origin"])?;
-        self.sync_run_git_command(&["checkout", "-b", branch, &format!("origin/{}", branch)])?;
+        let repository = LibGit::init(&self.get_path(), branch)?;
+        repository.add_remote(repo)?;
+
+        let progress = ProgressLine::default();
+        repository.fetch(&|transfer| progress.update(&transfer.label()))?;
+        progress.clear();
+
+        // a new folder only has an empty index, which would block checking out the remote one
+        let index = self.index_path();
+        if fs::metadata(&index).is_ok_and(|metadata| metadata.len() == 0) {
+            fs::remove_file(&index)?;
+        }
+
+        repository.track_branch(branch)?;
+        register_merge_driver(&repository)?;
 
-        println!("Set up git tracking!");
+        output::message("Set up git tracking!");
 
         Ok(())
     }
@@ -1,11 +1,14 @@
 use colored::Colorize;
-use std::io::Write;
 
 use thiserror::Error;
 
 use crate::{
-    actions::folder::{model::Folder, sync::setup::SetupSyncError},
+    actions::folder::{
+        model::Folder,
+        sync::{conflicts::ConflictError, git::GitError, merge::register_merge_driver},
+    },
     config::model::Config,
+    output,
 };
 
 #[derive(Error, Debug)]
@@ -14,35 +17,43 @@ pub enum SyncManualError {
     NoGitRepository,
 
     #[error(transparent)]
-    GitFailure(#[from] SetupSyncError),
+    Git(#[from] GitError),
 
     #[error(transparent)]
-    Io(#[from] std::io::Error),
+    Conflict(#[from] ConflictError),
 }
 
 type Error = SyncManualError;
 
 impl Folder {
     pub fn sync_manual(&self, config: &Config) -> Result<(), SyncManualError> {
-        if !self.sync_exists(config) {
-            return Err(Error::NoGitRepository);
-        }
+        let git_root = self.sync_git_root(config).ok_or(Error::NoGitRepository)?;
+        let repository = git_root.sync_repository()?;
+        // repositories set up by older versions don't have it yet
+        register_merge_driver(repository.as_ref())?;
 
-        print!("Pulling the latest changes for {}... ", self.name.blue());
-        std::io::stdout().flush()?;
+        // committed first, so that a merge never has to touch uncommitted notes
+        repository.stage_all()?;
+        let name = output::relative_path(&git_root.get_path(), &config.data_dir);
+        repository.commit(&config.commit_message("Sync", &name))?;
 
-        self.sync_run_git_command(&["pull"])?;
+        output::message_inline(&format!(
+            "Pulling the latest changes for {}... ",
+            self.name.blue()
+        ));
 
-        println!("{}", "Done".green());
+        git_root.sync_pull(config, repository.as_ref())?;
 
-        print!("Pushing up all local changes for {}... ", self.name.blue());
-        std::io::stdout().flush()?;
+        output::message(&"Done".green().to_string());
 
-        self.sync_run_git_command(&["add", "-A"])?;
-        self.sync_run_git_command_conf(&["commit", "-m", "[nb-rs] Manual Sync"], true)?;
-        self.sync_run_git_command(&["push"])?;
+        output::message_inline(&format!(
+            "Pushing up all local changes for {}... ",
+            self.name.blue()
+        ));
 
-        println!("{}", "Done".green());
+        git_root.sync_push(config, repository.as_ref())?;
+
+        output::message(&"Done".green().to_string());
 
         Ok(())
     }
@@ -0,0 +1,66 @@
+use std::{fs, path::Path};
+
+use thiserror::Error;
+use tracing::debug;
+
+use crate::{
+    actions::folder::{model::Folder, sync::git::GitError},
+    config::model::Config,
+};
+
+#[derive(Error, Debug)]
+pub enum SyncMoveError {
+    #[error("cannot move between different git repositories")]
+    CrossRepository,
+
+    #[error(transparent)]
+    Git(#[from] GitError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+type Error = SyncMoveError;
+
+impl Folder {
+    /// Moves `from` (inside this folder) to `to` (inside `destination`).
+    /// Stages the move when both live in the same repository, so that the history is kept.
+    /// Returns the git root if the move happened inside a repository.
+    pub fn sync_move(
+        &self,
+        destination: &Folder,
+        from: &Path,
+        to: &Path,
+        config: &Config,
+    ) -> Result<Option<Folder>, Error> {
+        let source_root = self.sync_git_root(config);
+        let destination_root = destination.sync_git_root(config);
+
+        match (source_root, destination_root) {
+            (None, None) => {
+                fs::rename(from, to)?;
+                Ok(None)
+            }
+            (Some(source_root), Some(destination_root))
+                if source_root.get_path() == destination_root.get_path() =>
+            {
+                let from_relative = source_root.sync_relative_path(from);
+                let to_relative = source_root.sync_relative_path(to);
+
+                let repository = source_root.sync_repository()?;
+                let tracked = repository.is_tracked(&from_relative)?;
+
+                fs::rename(from, to)?;
+
+                if tracked {
+                    repository.stage(&[from_relative, to_relative])?;
+                } else {
+                    debug!("'{}' is not tracked, moving it without git", from_relative);
+                }
+
+                Ok(Some(source_root))
+            }
+            _ => Err(Error::CrossRepository),
+        }
+    }
+}
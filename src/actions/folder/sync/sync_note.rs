@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use thiserror::Error;
 use tracing::debug;
@@ -7,23 +7,28 @@ use crate::{
     actions::{
         folder::{
             model::{Folder, FolderError},
-            sync::setup::SetupSyncError,
+            sync::{
+                conflicts::ConflictError,
+                git::GitError,
+                mutation::{Mutation, MutationAction},
+            },
         },
         note::model::Note,
     },
     config::model::Config,
+    output,
 };
 
 #[derive(Error, Debug)]
 pub enum SyncError {
     #[error(transparent)]
-    GitFailure(#[from] SetupSyncError),
+    Git(#[from] GitError),
 
     #[error(transparent)]
-    Folder(#[from] FolderError),
+    Conflict(#[from] ConflictError),
 
-    #[error("failed to find git folder. Reached root dir")]
-    GitNotFound,
+    #[error(transparent)]
+    Folder(#[from] FolderError),
 }
 
 pub enum SyncStatus {
@@ -35,56 +40,50 @@ pub enum SyncStatus {
 type Error = SyncError;
 
 impl Folder {
+    /// Commits an edit of `note` together with the index of its folder.
     pub fn sync_note(&self, note: &Note, config: &Config) -> Result<SyncStatus, Error> {
-        if !self.sync_exists(config) {
-            return Ok(SyncStatus::NotSetup);
-        }
+        self.sync_note_as(note, MutationAction::Edit, config)
+    }
 
-        if config.offline {
-            return Ok(SyncStatus::Skipped);
+    /// Like [`Folder::sync_note`], but records the change as `action`.
+    pub fn sync_note_as(
+        &self,
+        note: &Note,
+        action: MutationAction,
+        config: &Config,
+    ) -> Result<SyncStatus, Error> {
+        let mut paths = vec![note.get_path()];
+
+        if self.index_path().exists() {
+            paths.push(self.index_path());
         }
 
-        let folder_path = self.get_path();
-
-        let mut stripped_path = Vec::new();
+        debug!("Syncing {}", note.name);
 
-        let mut git_root = folder_path;
-
-        let root_data_dir = PathBuf::from(&config.data_dir);
-
-        loop {
-            if git_root == root_data_dir {
-                return Err(SyncError::GitNotFound);
-            }
-            let mut tmp = git_root.clone();
-            tmp.push(".git");
-
-            if tmp.exists() {
-                break;
-            }
+        let name = output::relative_path(&note.get_path(), &config.data_dir);
+        Mutation::new(action, name, paths).commit(config)
+    }
 
-            let base = git_root.file_name().unwrap().to_str().unwrap().to_string();
-            stripped_path.push(base);
-            git_root.pop();
-        }
+    /// Returns `path` relative to this folder, which is how git expects it when run from here.
+    pub fn sync_relative_path(&self, path: &Path) -> String {
+        let root = self.get_path();
+        let relative: PathBuf = path.strip_prefix(&root).unwrap_or(path).to_path_buf();
 
-        debug!("git root found: {}", git_root.to_str().unwrap());
-        debug!("stripped_path: {:#?}", stripped_path);
+        relative.to_str().unwrap().to_string()
+    }
 
-        let note_name = match stripped_path.is_empty() {
-            false => format!("{}/{}", stripped_path.join("/"), note.name),
-            true => note.name.clone(),
-        };
+    /// Stages `paths` and commits them with `message`. Paths that are gone are staged as
+    /// removals. Has to be called on the git root folder.
+    pub fn sync_commit(&self, paths: &[PathBuf], message: &str) -> Result<(), Error> {
+        let paths: Vec<String> = paths
+            .iter()
+            .map(|path| self.sync_relative_path(path))
+            .collect();
 
-        let git_root_folder = Folder::from_pathbuf(&git_root, ".")?;
+        let repository = self.sync_repository()?;
+        repository.stage(&paths)?;
+        repository.commit(message)?;
 
-        debug!("Syncing {}", note_name);
-        git_root_folder.sync_run_git_command(&["add", &note_name])?;
-        git_root_folder.sync_run_git_command_conf(
-            &["commit", "-m", &format!("[nb-rs] Edit: {}", note.name)],
-            true,
-        )?;
-        git_root_folder.sync_run_git_command(&["push"])?;
-        Ok(SyncStatus::Success)
+        Ok(())
     }
 }
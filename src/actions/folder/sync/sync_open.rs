@@ -0,0 +1,79 @@
+use colored::Colorize;
+use thiserror::Error;
+use tracing::debug;
+
+use crate::{
+    actions::{
+        folder::{model::Folder, sync::git::GitError},
+        note::model::Note,
+    },
+    config::model::Config,
+    output::{self, ProgressLine},
+};
+
+#[derive(Error, Debug)]
+pub enum SyncOpenError {
+    #[error(transparent)]
+    Git(#[from] GitError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+type Error = SyncOpenError;
+
+impl Folder {
+    /// Fast-forwards the repository of `note` to the remote before it's opened, so that the
+    /// editor doesn't start from an outdated copy. Returns `false` if that failed and the note
+    /// should not be opened after all.
+    pub fn sync_before_open(&self, note: &Note, config: &Config) -> Result<bool, Error> {
+        if config.offline || !config.pull_before_open {
+            return Ok(true);
+        }
+
+        let git_root = match self.sync_git_root(config) {
+            None => return Ok(true),
+            Some(value) => value,
+        };
+
+        debug!("Pulling before opening {}", note.name);
+
+        output::message_inline("Pulling the latest changes... ");
+
+        let progress = ProgressLine::default();
+        let pulled = git_root
+            .sync_repository()?
+            .fast_forward(&|transfer| progress.update(&transfer.label()));
+        progress.clear();
+
+        let e = match pulled {
+            Ok(status) => {
+                debug!("Pulled {:?}", status);
+                output::message(&"Done".green().to_string());
+                return Ok(true);
+            }
+            Err(e) => e,
+        };
+
+        output::message(&"Failed".red().to_string());
+        output::message(&format!("{}: {}", "Warning".yellow(), e));
+
+        let mut input = String::new();
+
+        output::message_inline(&format!(
+            "'{}' may be outdated. Open it anyway? {}/{} ",
+            note.name.blue(),
+            "y".red(),
+            "N".green()
+        ));
+
+        std::io::stdin().read_line(&mut input)?;
+
+        if input.trim() != "y" {
+            output::message("Aborting!");
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+use crate::actions::{
+    folder::{
+        list::{FolderSearchResult, ListFolderError},
+        model::Folder,
+    },
+    note::model::NoteError,
+    search_index::model::SearchIndex,
+};
+
+#[derive(Error, Debug)]
+pub enum ListTagsError {
+    #[error(transparent)]
+    ListFolder(#[from] ListFolderError),
+
+    #[error(transparent)]
+    Note(#[from] NoteError),
+}
+
+impl Folder {
+    /// Counts in how many notes below this folder each tag appears.
+    pub fn list_tags(&self, index: &SearchIndex) -> Result<BTreeMap<String, usize>, ListTagsError> {
+        let entries = self.list()?;
+        let mut tags = BTreeMap::new();
+
+        for note in entries.notes.iter() {
+            for tag in index.tags_of(note)? {
+                *tags.entry(tag).or_insert(0) += 1;
+            }
+        }
+
+        for folder in entries.folders.iter() {
+            for (tag, count) in folder.list_tags(index)? {
+                *tags.entry(tag).or_insert(0) += count;
+            }
+        }
+
+        Ok(tags)
+    }
+}
+
+impl FolderSearchResult {
+    /// Drops all notes that don't have every one of the given tags.
+    pub fn retain_tagged(&mut self, tags: &[String], index: &SearchIndex) -> Result<(), NoteError> {
+        if tags.is_empty() {
+            return Ok(());
+        }
+
+        let mut notes = Vec::new();
+        for note in self.notes.drain(..) {
+            if index.has_tags(&note, tags)? {
+                notes.push(note);
+            }
+        }
+
+        self.notes = notes;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use thiserror::Error;
+
+use crate::actions::{
+    folder::{list::ListFolderError, model::Folder},
+    note::{
+        model::{Note, NoteError},
+        tasks::Task,
+    },
+};
+
+#[derive(Error, Debug)]
+pub enum TasksError {
+    #[error(transparent)]
+    ListFolder(#[from] ListFolderError),
+
+    #[error(transparent)]
+    Note(#[from] NoteError),
+}
+
+type Error = TasksError;
+
+impl Folder {
+    /// Returns every checkbox item in the notes below this folder, in note order. Encrypted
+    /// notes are skipped.
+    pub fn list_tasks(&self) -> Result<Vec<(Note, Task)>, Error> {
+        let entries = self.list()?;
+        let mut tasks = Vec::new();
+
+        for note in entries.notes.into_iter() {
+            for task in note.get_tasks()? {
+                tasks.push((note.clone(), task));
+            }
+        }
+
+        for folder in entries.folders.iter() {
+            tasks.append(&mut folder.list_tasks()?);
+        }
+
+        Ok(tasks)
+    }
+}
@@ -0,0 +1,87 @@
+use chrono::{Local, NaiveDate};
+use thiserror::Error;
+
+use crate::{
+    actions::{
+        folder::{
+            list::ListFolderError,
+            model::Folder,
+            sync::{mutation::MutationAction, sync_note::SyncError},
+        },
+        note::{
+            create::NoteCreationError,
+            model::{Note, NoteError},
+            todo::{TODO_SUFFIX, Todo},
+        },
+    },
+    config::model::Config,
+};
+
+#[derive(Error, Debug)]
+pub enum TodosError {
+    #[error(transparent)]
+    ListFolder(#[from] ListFolderError),
+
+    #[error(transparent)]
+    Note(#[from] NoteError),
+
+    #[error(transparent)]
+    Create(#[from] NoteCreationError),
+
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+}
+
+type Error = TodosError;
+
+impl Folder {
+    /// Creates a todo note named after the current time, like nb does, and commits it. A short
+    /// random suffix keeps the name unique across machines.
+    pub fn todo_add(
+        &self,
+        config: &Config,
+        title: &str,
+        due: Option<NaiveDate>,
+    ) -> Result<Note, Error> {
+        let stem = Local::now().format("%Y%m%d%H%M%S").to_string();
+
+        // the random part keeps todos added on two machines in the same second apart
+        let name = loop {
+            let suffix = uuid::Uuid::new_v4().simple().to_string();
+            let name = format!("{}-{}{}", stem, &suffix[..6], TODO_SUFFIX);
+            if !self.get_path().join(&name).exists() {
+                break name;
+            }
+        };
+
+        let note = Note::new_create(
+            self.get_path().to_str().unwrap(),
+            &name,
+            None,
+            &Todo::serialize_new(title, due),
+            None,
+        )?;
+
+        self.sync_note_as(&note, MutationAction::Create, config)?;
+
+        Ok(note)
+    }
+
+    /// Returns every todo note below this folder together with its state.
+    pub fn list_todos(&self) -> Result<Vec<(Note, Todo)>, Error> {
+        let entries = self.list()?;
+        let mut todos = Vec::new();
+
+        for note in entries.notes.into_iter() {
+            if let Some(todo) = note.get_todo()? {
+                todos.push((note, todo));
+            }
+        }
+
+        for folder in entries.folders.iter() {
+            todos.append(&mut folder.list_todos()?);
+        }
+
+        Ok(todos)
+    }
+}
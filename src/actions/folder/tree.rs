@@ -0,0 +1,175 @@
+use std::path::Path;
+
+use colored::Colorize;
+use thiserror::Error;
+
+use crate::actions::{
+    folder::{
+        list::{FolderSearchResult, ListEntry, ListFolderError, note_label},
+        list_options::ListOptions,
+        model::Folder,
+    },
+    note::model::NoteError,
+    search_index::model::SearchIndex,
+};
+
+#[derive(Error, Debug)]
+pub enum FolderTreeError {
+    #[error(transparent)]
+    ListFolder(#[from] ListFolderError),
+
+    #[error(transparent)]
+    Note(#[from] NoteError),
+}
+
+/// A folder together with everything below it.
+pub struct FolderTree {
+    pub folder: Folder,
+    pub entries: FolderSearchResult,
+    pub children: Vec<FolderTree>,
+    /// Number of notes in this folder and all folders below it
+    pub note_count: usize,
+}
+
+impl Folder {
+    /// Lists this folder and all folders below it, the same way `get_notes_by_name` walks them,
+    /// so hidden folders are skipped. Only notes that have all `tags` are kept and `options` are
+    /// applied in every folder.
+    pub fn list_tree(
+        self,
+        tags: &[String],
+        options: &ListOptions,
+        index: &SearchIndex,
+    ) -> Result<FolderTree, FolderTreeError> {
+        let mut entries = self.list()?;
+        entries.retain_tagged(tags, index)?;
+        entries.apply(
+            &ListOptions {
+                notes_only: false,
+                folders_only: false,
+                ..options.clone()
+            },
+            index,
+        )?;
+
+        let mut children = Vec::new();
+        for folder in entries.folders.drain(..) {
+            children.push(folder.list_tree(tags, options, index)?);
+        }
+
+        let note_count =
+            entries.notes.len() + children.iter().map(|child| child.note_count).sum::<usize>();
+
+        // the counts still include the notes, only the lines for them are hidden
+        if options.folders_only {
+            entries.notes.clear();
+        }
+
+        Ok(FolderTree {
+            folder: self,
+            entries,
+            children,
+            note_count,
+        })
+    }
+}
+
+fn count_label(count: usize) -> String {
+    match count {
+        1 => "(1 note)".to_string(),
+        count => format!("({} notes)", count),
+    }
+}
+
+impl FolderTree {
+    /// Prints the tree with box-drawing characters. Folders deeper than `depth` are shown with
+    /// their note count but without their contents.
+    pub fn print(&self, index: &SearchIndex, depth: Option<usize>) {
+        let name = match self.folder.name.is_empty() {
+            true => ".".to_string(),
+            false => self.folder.name.clone(),
+        };
+
+        println!("{} {}", name.blue(), count_label(self.note_count).dimmed());
+        self.print_children(index, "", 1, depth);
+    }
+
+    fn print_children(
+        &self,
+        index: &SearchIndex,
+        prefix: &str,
+        level: usize,
+        depth: Option<usize>,
+    ) {
+        if depth.is_some_and(|depth| level > depth) {
+            return;
+        }
+
+        let total = self.children.len() + self.entries.notes.len();
+        let mut position = 0;
+
+        for child in self.children.iter() {
+            position += 1;
+            let last = position == total;
+
+            println!(
+                "{}{}{}/ {}",
+                prefix,
+                if last { "└── " } else { "├── " },
+                child.folder.name.blue(),
+                count_label(child.note_count).dimmed()
+            );
+
+            let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            child.print_children(index, &prefix, level + 1, depth);
+        }
+
+        for note in self.entries.notes.iter() {
+            position += 1;
+            let last = position == total;
+
+            println!(
+                "{}{}{}",
+                prefix,
+                if last { "└── " } else { "├── " },
+                note_label(note, index)
+            );
+        }
+    }
+
+    /// Flattens the tree into `ls` entries up to `depth`, folders before the notes in them.
+    pub fn entries(
+        &self,
+        root: &Path,
+        index: &SearchIndex,
+        depth: Option<usize>,
+    ) -> Result<Vec<ListEntry>, ListFolderError> {
+        let mut entries = Vec::new();
+        self.collect_entries(root, index, 1, depth, &mut entries)?;
+        Ok(entries)
+    }
+
+    fn collect_entries(
+        &self,
+        root: &Path,
+        index: &SearchIndex,
+        level: usize,
+        depth: Option<usize>,
+        entries: &mut Vec<ListEntry>,
+    ) -> Result<(), ListFolderError> {
+        if depth.is_some_and(|depth| level > depth) {
+            return Ok(());
+        }
+
+        for child in self.children.iter() {
+            entries.push(ListEntry::folder(&child.folder, root));
+            child.collect_entries(root, index, level + 1, depth, entries)?;
+        }
+
+        for note in self.entries.notes.iter() {
+            entries.push(ListEntry::note(note, root, index)?);
+        }
+
+        Ok(())
+    }
+}
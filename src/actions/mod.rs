@@ -1,2 +1,4 @@
 pub mod folder;
 pub mod note;
+pub mod search_index;
+pub mod trash;
@@ -1,10 +1,21 @@
 use colored::Colorize;
-use std::fs;
+use std::{fs, io::Write};
 
+use age::secrecy::SecretString;
 use thiserror::Error;
 use tracing::debug;
 
-use crate::actions::note::model::{Note, NoteError};
+use crate::{
+    actions::{
+        folder::{index::IndexError, model::Folder},
+        note::{
+            encryption::{EncryptionError, encrypt},
+            front_matter::{FrontMatter, FrontMatterError},
+            model::{Note, NoteError},
+        },
+    },
+    output,
+};
 
 #[derive(Error, Debug)]
 pub enum NoteCreationError {
@@ -16,20 +27,57 @@ pub enum NoteCreationError {
 
     #[error(transparent)]
     Note(#[from] NoteError),
+
+    #[error(transparent)]
+    Index(#[from] IndexError),
+
+    #[error(transparent)]
+    FrontMatter(#[from] FrontMatterError),
+
+    #[error(transparent)]
+    Encryption(#[from] EncryptionError),
 }
 
 impl Note {
-    pub(super) fn create(&self) -> Result<(), NoteCreationError> {
+    /// Creates the note file with an optional front matter followed by `body`. With a passphrase
+    /// the note is encrypted right away, so the plaintext never touches the disk.
+    pub(super) fn create(
+        &self,
+        front_matter: Option<&FrontMatter>,
+        body: &str,
+        passphrase: Option<&SecretString>,
+    ) -> Result<(), NoteCreationError> {
         let path = self.get_path();
         if path.exists() {
             return Err(NoteCreationError::NoteExists(self.name.clone()));
         }
 
-        fs::File::create_new(path.clone()).map_err(|e| NoteCreationError::Create(e.to_string()))?;
+        // built before the file exists, so that a failure doesn't leave an empty note behind
+        let content = match front_matter {
+            None => body.to_string(),
+            Some(front_matter) => format!("{}{}", front_matter.serialize()?, body),
+        };
+
+        let content = match passphrase {
+            None => content,
+            Some(passphrase) => encrypt(&content, passphrase)?,
+        };
+
+        let mut file = fs::File::create_new(path.clone())
+            .map_err(|e| NoteCreationError::Create(e.to_string()))?;
+
+        if let Err(e) = file.write_all(content.as_bytes()) {
+            drop(file);
+            let _ = fs::remove_file(&path);
+            return Err(NoteCreationError::Create(e.to_string()));
+        }
 
         debug!("Created note '{}'", path.to_str().unwrap());
 
-        println!("Created note '{}'", self.name.blue());
+        let id = Folder::from_note(self).index_add(&self.name)?;
+        debug!("Assigned id {} to '{}'", id, self.name);
+
+        output::message(&format!("Created note '{}'", self.name.blue()));
 
         Ok(())
     }
@@ -0,0 +1,136 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+use age::secrecy::{ExposeSecret, SecretString};
+use thiserror::Error;
+use tracing::debug;
+
+use crate::actions::note::model::Note;
+
+/// The extension appended to encrypted notes, e.g. `runbook.md.age`.
+pub const ENCRYPTED_EXTENSION: &str = "age";
+
+/// Read instead of prompting, for scripts.
+const PASSPHRASE_ENV: &str = "NB_RS_PASSPHRASE";
+
+#[derive(Error, Debug)]
+pub enum EncryptionError {
+    #[error("failed to encrypt the note: {0}")]
+    Encrypt(#[from] age::EncryptError),
+
+    #[error("failed to decrypt the note, is the passphrase correct?")]
+    Decrypt(#[from] age::DecryptError),
+
+    #[error("the passphrase can't be empty")]
+    EmptyPassphrase,
+
+    #[error("the passphrases don't match")]
+    PassphraseMismatch,
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+type Error = EncryptionError;
+
+/// Asks for the passphrase of an existing note.
+pub fn prompt_passphrase(prompt: &str) -> Result<SecretString, Error> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV) {
+        return Ok(SecretString::from(passphrase));
+    }
+
+    let passphrase = rpassword::prompt_password(prompt)?;
+    if passphrase.is_empty() {
+        return Err(Error::EmptyPassphrase);
+    }
+
+    Ok(SecretString::from(passphrase))
+}
+
+/// Asks for a new passphrase twice, so that a typo doesn't lock the note away.
+pub fn prompt_new_passphrase() -> Result<SecretString, Error> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV) {
+        return Ok(SecretString::from(passphrase));
+    }
+
+    let passphrase = prompt_passphrase("Passphrase for the new note: ")?;
+    let repeated = rpassword::prompt_password("Repeat the passphrase: ")?;
+
+    if passphrase.expose_secret() != repeated {
+        return Err(Error::PassphraseMismatch);
+    }
+
+    Ok(passphrase)
+}
+
+/// Encrypts `plaintext` with `passphrase` into an ASCII armored age file.
+pub fn encrypt(plaintext: &str, passphrase: &SecretString) -> Result<String, Error> {
+    let recipient = age::scrypt::Recipient::new(passphrase.clone());
+    Ok(age::encrypt_and_armor(&recipient, plaintext.as_bytes())?)
+}
+
+pub fn decrypt(ciphertext: &[u8], passphrase: &SecretString) -> Result<String, Error> {
+    let identity = age::scrypt::Identity::new(passphrase.clone());
+    let plaintext = age::decrypt(&identity, ciphertext)?;
+    Ok(String::from_utf8_lossy(&plaintext).into_owned())
+}
+
+/// Overwrites a file with zeros before deleting it, so that the plaintext doesn't linger on disk.
+pub fn shred(path: &Path) -> Result<(), Error> {
+    let length = fs::metadata(path)?.len();
+
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    file.write_all(&vec![0; length as usize])?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::remove_file(path)?;
+    debug!("Shredded {:?}", path);
+
+    Ok(())
+}
+
+/// Writes `content` to a new file that only the current user can read.
+pub fn write_private(path: &Path, content: &str) -> Result<(), Error> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file: File = options.open(path)?;
+    file.write_all(content.as_bytes())?;
+
+    Ok(())
+}
+
+impl Note {
+    pub fn is_encrypted(&self) -> bool {
+        Path::new(&self.name)
+            .extension()
+            .is_some_and(|ext| ext == ENCRYPTED_EXTENSION)
+    }
+
+    /// The name of the note without the encryption extension, e.g. `runbook.md`.
+    pub fn decrypted_name(&self) -> &str {
+        self.name
+            .strip_suffix(&format!(".{}", ENCRYPTED_EXTENSION))
+            .unwrap_or(&self.name)
+    }
+
+    pub fn decrypt_content(&self, passphrase: &SecretString) -> Result<String, Error> {
+        decrypt(&fs::read(self.get_path())?, passphrase)
+    }
+
+    pub fn write_encrypted(&self, content: &str, passphrase: &SecretString) -> Result<(), Error> {
+        fs::write(self.get_path(), encrypt(content, passphrase)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+use std::str::FromStr;
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrontMatterError {
+    #[error("failed to parse YAML front matter: {0}")]
+    Yaml(#[from] serde_yaml_ng::Error),
+
+    #[error("failed to parse TOML front matter: {0}")]
+    TomlParse(#[from] toml::de::Error),
+
+    #[error("failed to write TOML front matter: {0}")]
+    TomlWrite(#[from] toml::ser::Error),
+
+    #[error("the front matter is not a map of keys and values")]
+    NotAMap,
+}
+
+type Error = FrontMatterError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FrontMatterFormat {
+    #[default]
+    Yaml,
+    Toml,
+}
+
+impl FrontMatterFormat {
+    pub fn delimiter(&self) -> &'static str {
+        match self {
+            FrontMatterFormat::Yaml => "---",
+            FrontMatterFormat::Toml => "+++",
+        }
+    }
+}
+
+impl FromStr for FrontMatterFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "yaml" => Ok(FrontMatterFormat::Yaml),
+            "toml" => Ok(FrontMatterFormat::Toml),
+            other => Err(format!("unknown front matter format '{}'", other)),
+        }
+    }
+}
+
+/// Splits a note into the format and raw content of its front matter and the body after it.
+/// Returns `None` if the note doesn't start with a `---` or `+++` block.
+pub fn split_front_matter(content: &str) -> Option<(FrontMatterFormat, &str, &str)> {
+    let first_line_end = content.find('\n')?;
+
+    let format = match content[..first_line_end].trim_end() {
+        "---" => FrontMatterFormat::Yaml,
+        "+++" => FrontMatterFormat::Toml,
+        _ => return None,
+    };
+
+    let rest = &content[first_line_end + 1..];
+    let mut offset = 0;
+
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == format.delimiter() {
+            return Some((format, &rest[..offset], &rest[offset + line.len()..]));
+        }
+
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Returns `content` with its front matter replaced by `front_matter`, leaving the body untouched.
+/// An empty front matter removes the block entirely.
+pub fn replace_front_matter(content: &str, front_matter: &FrontMatter) -> Result<String, Error> {
+    let body = match split_front_matter(content) {
+        None => content,
+        Some((_, _, body)) => body,
+    };
+
+    Ok(match front_matter.is_empty() {
+        true => body.to_string(),
+        false => format!("{}{}", front_matter.serialize()?, body),
+    })
+}
+
+#[derive(Debug, Clone)]
+enum FrontMatterData {
+    Yaml(serde_yaml_ng::Mapping),
+    Toml(toml::Table),
+}
+
+/// The metadata block at the top of a note. The keys are kept in their original order so that
+/// editing a single key leaves the rest of the block alone.
+#[derive(Debug, Clone)]
+pub struct FrontMatter {
+    data: FrontMatterData,
+}
+
+impl FrontMatter {
+    pub fn new(format: FrontMatterFormat) -> Self {
+        let data = match format {
+            FrontMatterFormat::Yaml => FrontMatterData::Yaml(serde_yaml_ng::Mapping::new()),
+            FrontMatterFormat::Toml => FrontMatterData::Toml(toml::Table::new()),
+        };
+
+        Self { data }
+    }
+
+    pub fn parse(format: FrontMatterFormat, raw: &str) -> Result<Self, Error> {
+        let data = match format {
+            FrontMatterFormat::Yaml => {
+                match serde_yaml_ng::from_str::<serde_yaml_ng::Value>(raw)? {
+                    serde_yaml_ng::Value::Null => {
+                        FrontMatterData::Yaml(serde_yaml_ng::Mapping::new())
+                    }
+                    serde_yaml_ng::Value::Mapping(mapping) => FrontMatterData::Yaml(mapping),
+                    _ => return Err(Error::NotAMap),
+                }
+            }
+            FrontMatterFormat::Toml => FrontMatterData::Toml(raw.parse()?),
+        };
+
+        Ok(Self { data })
+    }
+
+    pub fn format(&self) -> FrontMatterFormat {
+        match self.data {
+            FrontMatterData::Yaml(_) => FrontMatterFormat::Yaml,
+            FrontMatterData::Toml(_) => FrontMatterFormat::Toml,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match &self.data {
+            FrontMatterData::Yaml(mapping) => mapping.is_empty(),
+            FrontMatterData::Toml(table) => table.is_empty(),
+        }
+    }
+
+    /// Returns the block including its delimiters, ready to be put in front of the body.
+    pub fn serialize(&self) -> Result<String, Error> {
+        let delimiter = self.format().delimiter();
+
+        let content = match &self.data {
+            FrontMatterData::Yaml(mapping) if mapping.is_empty() => String::new(),
+            FrontMatterData::Yaml(mapping) => serde_yaml_ng::to_string(mapping)?,
+            FrontMatterData::Toml(table) => toml::to_string(table)?,
+        };
+
+        Ok(format!("{}\n{}{}\n", delimiter, content, delimiter))
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        match &self.data {
+            FrontMatterData::Yaml(mapping) => mapping
+                .keys()
+                .filter_map(|key| key.as_str().map(|key| key.to_string()))
+                .collect(),
+            FrontMatterData::Toml(table) => table.keys().cloned().collect(),
+        }
+    }
+
+    /// Returns the value of `key` in a human readable form. Strings are returned without quotes
+    /// and lists of strings are joined with commas.
+    pub fn get(&self, key: &str) -> Option<String> {
+        let list = self.get_list(key);
+        if !list.is_empty() && !self.is_string(key) {
+            return Some(list.join(", "));
+        }
+
+        match &self.data {
+            FrontMatterData::Yaml(mapping) => match mapping.get(key)? {
+                serde_yaml_ng::Value::String(value) => Some(value.clone()),
+                serde_yaml_ng::Value::Null => None,
+                other => serde_yaml_ng::to_string(other)
+                    .ok()
+                    .map(|value| value.trim_end().to_string()),
+            },
+            FrontMatterData::Toml(table) => match table.get(key)? {
+                toml::Value::String(value) => Some(value.clone()),
+                other => Some(other.to_string()),
+            },
+        }
+    }
+
+    fn is_string(&self, key: &str) -> bool {
+        match &self.data {
+            FrontMatterData::Yaml(mapping) => {
+                mapping.get(key).is_some_and(|value| value.is_string())
+            }
+            FrontMatterData::Toml(table) => table.get(key).is_some_and(|value| value.is_str()),
+        }
+    }
+
+    /// Returns `key` as a list of strings. A single string is split at commas.
+    pub fn get_list(&self, key: &str) -> Vec<String> {
+        let strings: Vec<String> = match &self.data {
+            FrontMatterData::Yaml(mapping) => match mapping.get(key) {
+                Some(serde_yaml_ng::Value::Sequence(values)) => values
+                    .iter()
+                    .filter_map(|value| value.as_str().map(|value| value.to_string()))
+                    .collect(),
+                Some(serde_yaml_ng::Value::String(value)) => vec![value.clone()],
+                _ => Vec::new(),
+            },
+            FrontMatterData::Toml(table) => match table.get(key) {
+                Some(toml::Value::Array(values)) => values
+                    .iter()
+                    .filter_map(|value| value.as_str().map(|value| value.to_string()))
+                    .collect(),
+                Some(toml::Value::String(value)) => vec![value.clone()],
+                _ => Vec::new(),
+            },
+        };
+
+        strings
+            .iter()
+            .flat_map(|value| value.split(','))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_string())
+            .collect()
+    }
+
+    pub fn title(&self) -> Option<String> {
+        self.get("title")
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty())
+    }
+
+    /// Returns the `tags` list. Unlike other lists, tags can also be separated by spaces.
+    pub fn tags(&self) -> Vec<String> {
+        self.get_list("tags")
+            .iter()
+            .flat_map(|tags| tags.split_whitespace())
+            .map(|tag| tag.to_string())
+            .collect()
+    }
+
+    pub fn aliases(&self) -> Vec<String> {
+        self.get_list("aliases")
+    }
+
+    pub fn created(&self) -> Option<String> {
+        self.get("created")
+    }
+
+    pub fn updated(&self) -> Option<String> {
+        self.get("updated")
+    }
+
+    /// Sets `key` to `value`. The value is interpreted like it would be if it was written in the
+    /// front matter directly, so `[a, b]` becomes a list and `true` a boolean. Anything that
+    /// doesn't parse is stored as a string.
+    pub fn set(&mut self, key: &str, value: &str) {
+        match &mut self.data {
+            FrontMatterData::Yaml(mapping) => {
+                let value = serde_yaml_ng::from_str::<serde_yaml_ng::Value>(value)
+                    .ok()
+                    .filter(|value| !value.is_null())
+                    .unwrap_or(serde_yaml_ng::Value::String(value.to_string()));
+
+                mapping.insert(serde_yaml_ng::Value::String(key.to_string()), value);
+            }
+            FrontMatterData::Toml(table) => {
+                let value = format!("value = {}", value)
+                    .parse::<toml::Table>()
+                    .ok()
+                    .and_then(|mut parsed| parsed.remove("value"))
+                    .unwrap_or(toml::Value::String(value.to_string()));
+
+                table.insert(key.to_string(), value);
+            }
+        }
+    }
+
+    /// Sets `key` to a list of strings.
+    pub fn set_list(&mut self, key: &str, values: &[String]) {
+        match &mut self.data {
+            FrontMatterData::Yaml(mapping) => {
+                let values = values
+                    .iter()
+                    .map(|value| serde_yaml_ng::Value::String(value.clone()))
+                    .collect();
+
+                mapping.insert(
+                    serde_yaml_ng::Value::String(key.to_string()),
+                    serde_yaml_ng::Value::Sequence(values),
+                );
+            }
+            FrontMatterData::Toml(table) => {
+                let values = values
+                    .iter()
+                    .map(|value| toml::Value::String(value.clone()))
+                    .collect();
+
+                table.insert(key.to_string(), toml::Value::Array(values));
+            }
+        }
+    }
+
+    /// Removes `key`. Returns false if it wasn't set.
+    pub fn unset(&mut self, key: &str) -> bool {
+        match &mut self.data {
+            FrontMatterData::Yaml(mapping) => mapping.shift_remove(key).is_some(),
+            FrontMatterData::Toml(table) => table.remove(key).is_some(),
+        }
+    }
+}
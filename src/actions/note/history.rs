@@ -0,0 +1,236 @@
+use std::fs;
+
+use age::secrecy::SecretString;
+use colored::Colorize;
+use thiserror::Error;
+
+use crate::{
+    actions::{
+        folder::{
+            model::Folder,
+            sync::{
+                git::{GitError, GitRepository},
+                mutation::{Mutation, MutationAction},
+                sync_note::SyncError,
+            },
+        },
+        note::{
+            encryption::{EncryptionError, decrypt},
+            model::Note,
+        },
+    },
+    config::model::Config,
+    output,
+};
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("'{0}' is not in a synced folder, so it has no history")]
+    NotTracked(String),
+
+    #[error("'{0}' has no revisions yet")]
+    NoRevisions(String),
+
+    #[error("the note {0} is encrypted, compare the revisions with show --rev instead")]
+    Encrypted(String),
+
+    #[error(transparent)]
+    Git(#[from] GitError),
+
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+
+    #[error(transparent)]
+    Encryption(#[from] EncryptionError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+type Error = HistoryError;
+
+/// A commit that changed a note.
+#[derive(Debug, Clone)]
+pub struct Revision {
+    pub hash: String,
+    pub short_hash: String,
+    /// Where the note was in this revision, relative to the repository. Differs from the
+    /// current path for revisions from before a move.
+    pub path: String,
+    /// RFC3339, as written by git
+    pub date: String,
+    pub author: String,
+    pub message: String,
+    /// `None` for binary files, like encrypted notes
+    pub added: Option<usize>,
+    pub removed: Option<usize>,
+}
+
+impl Revision {
+    /// Formats the revision as `abc1234 2026-01-31 10:00 Alice +3 -1 [nb-rs] Edit: todo.md`.
+    pub fn label(&self) -> String {
+        let date = self.date.get(..16).unwrap_or(&self.date).replace('T', " ");
+
+        let changes = match (self.added, self.removed) {
+            (Some(added), Some(removed)) => format!(
+                "{} {}",
+                format!("+{}", added).green(),
+                format!("-{}", removed).red()
+            ),
+            _ => "binary".dimmed().to_string(),
+        };
+
+        format!(
+            "{} {} {} {} {}",
+            self.short_hash.yellow(),
+            date,
+            self.author.blue(),
+            changes,
+            self.message
+        )
+    }
+}
+
+impl Note {
+    /// The git repository of the note and the path of the note in it.
+    fn history_location(&self, config: &Config) -> Result<(Box<dyn GitRepository>, String), Error> {
+        let git_root = Folder::from_note(self)
+            .sync_git_root(config)
+            .ok_or_else(|| Error::NotTracked(self.name.clone()))?;
+
+        let path = git_root.sync_relative_path(&self.get_path());
+
+        Ok((git_root.sync_repository()?, path))
+    }
+
+    /// Returns the commits that changed the note, newest first. Renames are followed.
+    pub fn history(&self, config: &Config) -> Result<Vec<Revision>, Error> {
+        let (repository, path) = self.history_location(config)?;
+
+        Ok(repository.log(&path)?)
+    }
+
+    /// Returns the note as it was in `revision`. Encrypted notes are decrypted with `passphrase`.
+    pub fn show_revision(
+        &self,
+        config: &Config,
+        revision: &str,
+        passphrase: Option<&SecretString>,
+    ) -> Result<String, Error> {
+        let (repository, path) = self.history_location(config)?;
+        let path = repository.path_at(&path, revision)?;
+        let content = repository.file_at(revision, &path)?;
+
+        Ok(match passphrase {
+            None => String::from_utf8_lossy(&content).into_owned(),
+            Some(passphrase) => decrypt(&content, passphrase)?,
+        })
+    }
+
+    /// Returns the diff of the note between two revisions. Without `to` it's compared to the
+    /// current file, without either it's the change made by the latest revision.
+    pub fn diff(
+        &self,
+        config: &Config,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Result<String, Error> {
+        if self.is_encrypted() {
+            return Err(Error::Encrypted(self.name.clone()));
+        }
+
+        let (repository, path) = self.history_location(config)?;
+
+        let diff = match from {
+            None => {
+                let latest = repository
+                    .log(&path)?
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| Error::NoRevisions(self.name.clone()))?;
+
+                repository.diff_commit(&latest.hash, &path)?
+            }
+            Some(from) => repository.diff(from, to, &path)?,
+        };
+
+        Ok(diff)
+    }
+
+    /// Writes the note as it was in `revision` and commits that as a new revision.
+    pub fn restore(&self, config: &Config, revision: &str) -> Result<(), Error> {
+        let content = self.show_revision(config, revision, None)?;
+
+        fs::write(self.get_path(), content)?;
+        output::message(&format!(
+            "Restored '{}' to {}",
+            self.name.blue(),
+            revision.yellow()
+        ));
+
+        let name = output::relative_path(&self.get_path(), &config.data_dir);
+        Mutation::new(
+            MutationAction::Restore,
+            format!("{} to {}", name, revision),
+            vec![self.get_path()],
+        )
+        .commit(config)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::actions::folder::sync::libgit::LibGit;
+
+    fn commit(repository: &LibGit, paths: &[&str], message: &str) {
+        let paths: Vec<String> = paths.iter().map(|path| path.to_string()).collect();
+        repository.stage(&paths).unwrap();
+        repository.commit(message).unwrap();
+    }
+
+    #[test]
+    fn revisions_from_before_a_move_can_be_shown_and_restored() {
+        let directory = TempDir::new().unwrap();
+        let notes = directory.path().join("notes");
+        fs::create_dir(&notes).unwrap();
+
+        let repository = LibGit::init(&notes, "main").unwrap();
+        let mut git_config = Repository::open(&notes).unwrap().config().unwrap();
+        git_config.set_str("user.name", "Alice").unwrap();
+        git_config
+            .set_str("user.email", "alice@example.com")
+            .unwrap();
+
+        fs::write(notes.join("old.md"), "first\n").unwrap();
+        commit(&repository, &["old.md"], "Create: old.md");
+        fs::rename(notes.join("old.md"), notes.join("new.md")).unwrap();
+        commit(&repository, &["old.md", "new.md"], "Move: old.md -> new.md");
+        fs::write(notes.join("new.md"), "second\n").unwrap();
+        commit(&repository, &["new.md"], "Edit: new.md");
+
+        let config = Config::for_tests(directory.path());
+        let note = Note {
+            path: notes.to_str().unwrap().to_string(),
+            name: "new.md".to_string(),
+            id: None,
+        };
+
+        let history = note.history(&config).unwrap();
+        let created = &history.last().unwrap().short_hash;
+
+        assert_eq!(
+            note.show_revision(&config, created, None).unwrap(),
+            "first\n"
+        );
+
+        note.restore(&config, created).unwrap();
+        assert_eq!(fs::read_to_string(notes.join("new.md")).unwrap(), "first\n");
+        assert_eq!(note.history(&config).unwrap().len(), 4);
+    }
+}
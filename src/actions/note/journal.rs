@@ -0,0 +1,214 @@
+use std::{
+    fmt::Write,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
+
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use thiserror::Error;
+
+use crate::{
+    actions::{
+        folder::{
+            create::FolderCreationError,
+            model::{Folder, FolderError},
+            sync::{mutation::MutationAction, sync_note::SyncError},
+        },
+        note::{
+            create::NoteCreationError,
+            model::{Note, NoteError},
+            template::{Template, TemplateContext, TemplateError},
+        },
+    },
+    config::model::Config,
+};
+
+#[derive(Error, Debug)]
+pub enum JournalError {
+    #[error("invalid journal path pattern '{0}'")]
+    InvalidPattern(String),
+
+    #[error("the date is out of range")]
+    DateOutOfRange,
+
+    #[error(transparent)]
+    Folder(#[from] FolderError),
+
+    #[error(transparent)]
+    CreateFolder(#[from] FolderCreationError),
+
+    #[error(transparent)]
+    Create(#[from] NoteCreationError),
+
+    #[error(transparent)]
+    Note(#[from] NoteError),
+
+    #[error(transparent)]
+    Template(#[from] TemplateError),
+
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+}
+
+type Error = JournalError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// How many periods to move away from the given date, e.g. `yesterday`, `next` or `-2`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct JournalOffset(pub i32);
+
+impl FromStr for JournalOffset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "today" | "this" | "current" => Ok(JournalOffset(0)),
+            "yesterday" | "previous" | "prev" | "last" => Ok(JournalOffset(-1)),
+            "tomorrow" | "next" => Ok(JournalOffset(1)),
+            other => other.trim_start_matches('+').parse().map(JournalOffset).map_err(|_| {
+                format!(
+                    "unknown offset '{}', expected today, yesterday, tomorrow, previous, next or a number like -2",
+                    other
+                )
+            }),
+        }
+    }
+}
+
+impl Period {
+    /// The name of the command and of the template file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Period::Daily => "daily",
+            Period::Weekly => "weekly",
+            Period::Monthly => "monthly",
+        }
+    }
+
+    fn pattern<'a>(&self, config: &'a Config) -> &'a str {
+        match self {
+            Period::Daily => &config.journal_daily,
+            Period::Weekly => &config.journal_weekly,
+            Period::Monthly => &config.journal_monthly,
+        }
+    }
+
+    /// The first day of the period `date` is in. Weeks start on Monday.
+    pub fn start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Daily => date,
+            Period::Weekly => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Period::Monthly => date.with_day(1).unwrap(),
+        }
+    }
+
+    /// Moves `date` by `offset` periods.
+    pub fn shift(&self, date: NaiveDate, offset: JournalOffset) -> Result<NaiveDate, Error> {
+        let offset = offset.0;
+
+        let shifted = match self {
+            Period::Daily => date.checked_add_signed(Duration::days(offset as i64)),
+            Period::Weekly => date.checked_add_signed(Duration::weeks(offset as i64)),
+            Period::Monthly => match offset >= 0 {
+                true => date.checked_add_months(Months::new(offset as u32)),
+                false => date.checked_sub_months(Months::new(offset.unsigned_abs())),
+            },
+        };
+
+        shifted.ok_or(Error::DateOutOfRange)
+    }
+
+    /// The heading of a new journal note, e.g. `2026-01-31`, `2026 Week 5` or `January 2026`.
+    pub fn title(&self, start: NaiveDate) -> String {
+        match self {
+            Period::Daily => start.format("%Y-%m-%d").to_string(),
+            Period::Weekly => format!(
+                "{} Week {}",
+                start.iso_week().year(),
+                start.iso_week().week()
+            ),
+            Period::Monthly => start.format("%B %Y").to_string(),
+        }
+    }
+
+    /// The path of the journal note relative to the data dir, from the configured pattern.
+    fn path(&self, config: &Config, start: NaiveDate) -> Result<PathBuf, Error> {
+        let pattern = self.pattern(config);
+        let invalid = || Error::InvalidPattern(pattern.to_string());
+
+        let mut formatted = String::new();
+        write!(formatted, "{}", start.format(pattern)).map_err(|_| invalid())?;
+
+        let path = PathBuf::from(config.note_file_name(&formatted));
+        let is_relative = path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+
+        if !is_relative || path.file_name().is_none() {
+            return Err(invalid());
+        }
+
+        Ok(path)
+    }
+
+    /// The content of a new journal note: the `daily`, `weekly` or `monthly` template if it
+    /// exists, a heading otherwise. `{{date}}` is the first day of the period.
+    fn initial_content(
+        &self,
+        config: &Config,
+        folder: &str,
+        start: NaiveDate,
+    ) -> Result<String, Error> {
+        let title = self.title(start);
+
+        let template = match Template::load_optional(&config.data_dir, self.name())? {
+            None => return Ok(format!("# {}\n\n", title)),
+            Some(value) => value,
+        };
+
+        let mut context = TemplateContext::new(title, folder);
+        context.date = start;
+
+        Ok(template.render(&context)?)
+    }
+}
+
+impl Note {
+    /// Returns the journal note of the period that `date` is in. If it doesn't exist yet, it
+    /// is created together with its folders and seeded from the template. The flag tells
+    /// whether the note is new.
+    pub fn journal(
+        config: &Config,
+        period: Period,
+        date: NaiveDate,
+    ) -> Result<(Self, bool), Error> {
+        let start = period.start(date);
+        let path = period.path(config, start)?;
+
+        if let Ok(note) = Note::from_pathbuf(&config.data_dir, path.to_str().unwrap().to_string()) {
+            return Ok((note, false));
+        }
+
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let folder = Folder::from_pathbuf(&config.data_dir, directory.to_str().unwrap())?;
+        folder.create_all()?;
+
+        let content = period.initial_content(config, &folder.name, start)?;
+        let note = Note::new_create(
+            folder.get_path().to_str().unwrap(),
+            path.file_name().unwrap().to_str().unwrap(),
+            None,
+            &content,
+            None,
+        )?;
+        folder.sync_note_as(&note, MutationAction::Create, config)?;
+
+        Ok((note, true))
+    }
+}
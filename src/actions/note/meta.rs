@@ -0,0 +1,69 @@
+use colored::Colorize;
+use thiserror::Error;
+
+use crate::{
+    actions::{
+        folder::{model::Folder, sync::sync_note::SyncError},
+        note::{
+            front_matter::FrontMatter,
+            model::{Note, NoteError},
+        },
+    },
+    config::model::Config,
+    output,
+};
+
+#[derive(Error, Debug)]
+pub enum MetaError {
+    #[error("'{0}' is not set")]
+    KeyNotSet(String),
+
+    #[error(transparent)]
+    Note(#[from] NoteError),
+
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+}
+
+type Error = MetaError;
+
+impl Note {
+    /// Sets a front matter key, creating a YAML front matter if the note has none yet.
+    pub fn meta_set(&self, config: &Config, key: &str, value: &str) -> Result<(), Error> {
+        let mut front_matter = self
+            .get_front_matter()?
+            .unwrap_or(FrontMatter::new(Default::default()));
+
+        front_matter.set(key, value);
+        self.set_front_matter(&front_matter)?;
+
+        output::message(&format!("Set '{}' of '{}'", key.blue(), self.name.blue()));
+
+        Folder::from_note(self).sync_note(self, config)?;
+
+        Ok(())
+    }
+
+    pub fn meta_unset(&self, config: &Config, key: &str) -> Result<(), Error> {
+        let mut front_matter = match self.get_front_matter()? {
+            None => return Err(Error::KeyNotSet(key.to_string())),
+            Some(value) => value,
+        };
+
+        if !front_matter.unset(key) {
+            return Err(Error::KeyNotSet(key.to_string()));
+        }
+
+        self.set_front_matter(&front_matter)?;
+
+        output::message(&format!(
+            "Removed '{}' from '{}'",
+            key.blue(),
+            self.name.blue()
+        ));
+
+        Folder::from_note(self).sync_note(self, config)?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,14 @@
 pub mod create;
+pub mod encryption;
+pub mod front_matter;
+pub mod history;
+pub mod journal;
+pub mod meta;
 pub mod model;
 pub mod open;
 pub mod remove;
+pub mod rename;
+pub mod tags;
+pub mod tasks;
+pub mod template;
+pub mod todo;
@@ -1,13 +1,28 @@
 use std::{
-    fs::{self, File},
-    io::{self, BufRead, BufReader, Lines},
+    fs,
     path::{Path, PathBuf},
 };
 
+use age::secrecy::SecretString;
 use thiserror::Error;
 use tracing::debug;
 
-use crate::actions::note::create::NoteCreationError;
+use crate::{
+    actions::{
+        folder::{
+            index::IndexError,
+            model::{Folder, FolderError},
+        },
+        note::{
+            create::NoteCreationError,
+            front_matter::{
+                FrontMatter, FrontMatterError, replace_front_matter, split_front_matter,
+            },
+            tags::is_heading,
+        },
+    },
+    output,
+};
 
 #[derive(Error, Debug)]
 pub enum NoteError {
@@ -19,12 +34,27 @@ pub enum NoteError {
 
     #[error(transparent)]
     ReadError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Folder(#[from] FolderError),
+
+    #[error(transparent)]
+    Index(#[from] IndexError),
+
+    #[error(transparent)]
+    FrontMatter(#[from] FrontMatterError),
+
+    #[error("the note {0} is encrypted, open it to edit it")]
+    Encrypted(String),
 }
 
 #[derive(Clone, Debug)]
 pub struct Note {
     pub path: String,
     pub name: String,
+
+    /// The id of the note in its folder's index, if it has been looked up
+    pub id: Option<usize>,
 }
 
 impl Note {
@@ -33,6 +63,7 @@ impl Note {
         let note = Note {
             path,
             name: name.clone(),
+            id: None,
         };
 
         if !note.exists() || !note.get_path().is_file() {
@@ -43,14 +74,22 @@ impl Note {
         Ok(note)
     }
 
-    pub fn new_create(path: impl ToString, name: impl ToString) -> Result<Self, NoteCreationError> {
+    /// Creates a new note containing `body`. If `front_matter` is given, it is written above it.
+    pub fn new_create(
+        path: impl ToString,
+        name: impl ToString,
+        front_matter: Option<&FrontMatter>,
+        body: &str,
+        passphrase: Option<&SecretString>,
+    ) -> Result<Self, NoteCreationError> {
         let (path, name) = Note::normalize_name(path.to_string(), name.to_string())?;
         let note = Note {
             path: path.to_string(),
             name: name.to_string(),
+            id: None,
         };
 
-        note.create()?;
+        note.create(front_matter, body, passphrase)?;
 
         Ok(note)
     }
@@ -86,6 +125,55 @@ impl Note {
         Note::new(path, name)
     }
 
+    /// Resolves a note either by its path or by its id, e.g. `work/todo.md` or `work/12`.
+    /// A file that is literally named like the id takes precedence.
+    pub fn from_selector(data_dir: &Path, selector: &str) -> Result<Self, NoteError> {
+        match Note::from_pathbuf(data_dir, selector.to_string()) {
+            Err(NoteError::NoteDoesNotExist(_)) => {}
+            other => return other,
+        }
+
+        let (folder, id) = selector.rsplit_once('/').unwrap_or((".", selector));
+
+        let id: usize = match id.parse() {
+            Ok(value) => value,
+            Err(_) => return Err(NoteError::NoteDoesNotExist(selector.to_string())),
+        };
+
+        let folder = Folder::from_pathbuf(data_dir, folder)?;
+        if !folder.get_path().is_dir() {
+            return Err(NoteError::NoteDoesNotExist(selector.to_string()));
+        }
+
+        let index = folder.index_refresh()?;
+
+        let name = match index.name_of(id) {
+            None => return Err(NoteError::NoteDoesNotExist(selector.to_string())),
+            Some(value) => value,
+        };
+
+        let mut note = Note::from_pathbuf(&folder.get_path(), name.to_string())?;
+        note.id = Some(id);
+
+        Ok(note)
+    }
+
+    /// The reverse of [`Note::from_selector`]: `work/12` for a note with an id, the path
+    /// relative to the data directory otherwise.
+    pub fn selector(&self, data_dir: &Path) -> String {
+        let folder = output::relative_path(Path::new(&self.path), data_dir);
+
+        let name = match self.id {
+            Some(id) => id.to_string(),
+            None => self.name.clone(),
+        };
+
+        match folder.is_empty() {
+            true => name,
+            false => format!("{}/{}", folder, name),
+        }
+    }
+
     #[inline(always)]
     pub fn get_path(&self) -> PathBuf {
         let mut path = PathBuf::new();
@@ -101,27 +189,126 @@ impl Note {
         self.get_path().exists()
     }
 
-    pub fn get_content_by_lines(&self) -> Result<Lines<BufReader<File>>, NoteError> {
-        let file = fs::File::open(self.get_path())?;
-        Ok(io::BufReader::new(file).lines())
+    /// Reads the whole note. Invalid UTF-8 is replaced instead of failing.
+    pub fn get_content(&self) -> Result<String, NoteError> {
+        let bytes = fs::read(self.get_path())?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Returns the parsed front matter, or `None` if the note doesn't have any.
+    pub fn get_front_matter(&self) -> Result<Option<FrontMatter>, NoteError> {
+        let content = self.get_content()?;
+
+        match split_front_matter(&content) {
+            None => Ok(None),
+            Some((format, raw, _)) => Ok(Some(FrontMatter::parse(format, raw)?)),
+        }
+    }
+
+    /// Replaces the front matter of the note, leaving the body untouched.
+    /// An empty front matter removes the block entirely.
+    pub fn set_front_matter(&self, front_matter: &FrontMatter) -> Result<(), NoteError> {
+        if self.is_encrypted() {
+            return Err(NoteError::Encrypted(self.name.clone()));
+        }
+
+        let content = self.get_content()?;
+        fs::write(
+            self.get_path(),
+            replace_front_matter(&content, front_matter)?,
+        )?;
+
+        Ok(())
     }
 
+    /// Returns the front matter title, or the first `# ` heading of the body.
     pub fn get_pretty_name(&self) -> Result<Option<String>, NoteError> {
-        let lines = self.get_content_by_lines()?;
+        Ok(parse_pretty_name(&self.get_content()?))
+    }
 
-        for line in lines.map_while(Result::ok) {
-            if line.starts_with("# ") {
-                let line = line.strip_prefix("# ").unwrap();
-                let line = line.trim();
-                return Ok(Some(line.to_string()));
-            }
+    /// Returns true if the name or one of the front matter aliases contains `name`.
+    /// `name` has to be lowercase.
+    pub fn matches_name(&self, name: &str) -> Result<bool, NoteError> {
+        if self.get_name()?.to_lowercase().contains(name) {
+            return Ok(true);
         }
 
-        Ok(None)
+        let aliases = match self.get_front_matter() {
+            Ok(Some(front_matter)) => front_matter.aliases(),
+            _ => return Ok(false),
+        };
+
+        Ok(aliases
+            .iter()
+            .any(|alias| alias.to_lowercase().contains(name)))
     }
 
-    /// Returns the pretty name if available, otherwise just the file name
+    /// Returns the pretty name (front matter title or heading) if available, otherwise just the
+    /// file name
     pub fn get_name(&self) -> Result<String, NoteError> {
         Ok(self.get_pretty_name()?.unwrap_or(self.name.clone()))
     }
 }
+
+/// Returns the front matter title, or the first `# ` heading of the body.
+pub fn parse_pretty_name(content: &str) -> Option<String> {
+    let body = match split_front_matter(content) {
+        None => content,
+        Some((format, raw, body)) => {
+            // a broken front matter shouldn't stop us from showing the note
+            if let Some(title) = FrontMatter::parse(format, raw)
+                .ok()
+                .and_then(|front_matter| front_matter.title())
+            {
+                return Some(title);
+            }
+
+            body
+        }
+    };
+
+    for line in body.lines() {
+        if line.starts_with("# ") {
+            let line = line.strip_prefix("# ").unwrap();
+            let line = line.trim();
+            return Some(line.to_string());
+        }
+    }
+
+    None
+}
+
+/// Returns the text of every markdown heading in the body. Headings in fenced code blocks are
+/// skipped, since those are usually shell comments.
+pub fn parse_headings(content: &str) -> Vec<String> {
+    let body = split_front_matter(content)
+        .map(|(_, _, body)| body)
+        .unwrap_or(content);
+
+    let mut headings = Vec::new();
+    let mut fence: Option<&str> = None;
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+
+        if let Some(current) = fence {
+            if trimmed.starts_with(current) {
+                fence = None;
+            }
+            continue;
+        }
+
+        if trimmed.starts_with("```") {
+            fence = Some("```");
+        } else if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+        } else if is_heading(trimmed) {
+            let heading = trimmed.trim_start_matches('#').trim();
+            if !heading.is_empty() {
+                headings.push(heading.to_string());
+            }
+        }
+    }
+
+    headings
+}
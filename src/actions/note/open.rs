@@ -1,5 +1,11 @@
-use std::{fs::File, io::Write, process::Command};
+use std::{
+    fs::{self, File},
+    path::Path,
+    process::Command,
+};
 
+use age::secrecy::SecretString;
+use chrono::Local;
 use colored::Colorize;
 use thiserror::Error;
 use tracing::debug;
@@ -8,13 +14,23 @@ use crate::{
     actions::{
         folder::{
             model::Folder,
-            sync::sync_note::{SyncError, SyncStatus},
+            sync::{sync_note::SyncError, sync_open::SyncOpenError},
+        },
+        note::{
+            encryption::{EncryptionError, prompt_passphrase, shred, write_private},
+            front_matter::{FrontMatter, replace_front_matter, split_front_matter},
+            model::{Note, NoteError},
         },
-        note::model::Note,
     },
     config::model::Config,
+    output,
 };
 
+/// The current time in the format used for the `created` and `updated` front matter keys.
+pub fn timestamp() -> String {
+    Local::now().format("%Y-%m-%dT%H:%M:%S%:z").to_string()
+}
+
 #[derive(Error, Debug)]
 pub enum OpenNoteError {
     #[error("failed to run editor command: {0}")]
@@ -29,54 +45,125 @@ pub enum OpenNoteError {
 
     #[error(transparent)]
     GitFailure(#[from] SyncError),
+
+    #[error(transparent)]
+    Pull(#[from] SyncOpenError),
+
+    #[error(transparent)]
+    Note(#[from] NoteError),
+
+    #[error(transparent)]
+    Encryption(#[from] EncryptionError),
 }
 
 impl Note {
+    /// Sets the `updated` front matter key to the current time, but only if the note uses it.
+    fn touch_updated(&self) -> Result<(), NoteError> {
+        if let Some(content) = touch_updated_content(&self.get_content()?) {
+            fs::write(self.get_path(), content)?;
+        }
+
+        Ok(())
+    }
+
     pub fn open(&self, config: &Config) -> Result<(), OpenNoteError> {
+        if !Folder::from_note(self).sync_before_open(self, config)? {
+            return Ok(());
+        }
+
+        if self.is_encrypted() {
+            let passphrase =
+                prompt_passphrase(&format!("Passphrase for '{}': ", self.name.blue()))?;
+            return self.open_encrypted(config, &passphrase);
+        }
+
         let path = self.get_path();
 
         let file = File::open(&path)?;
         let old_modified = file.metadata()?.modified()?;
         drop(file);
 
-        let mut process = Command::new(&config.editor);
-        process.arg(&path);
-
-        debug!(
-            "Executing {:?} with '{:?}'",
-            process.get_program(),
-            process.get_args()
-        );
-        process
-            .status()
-            .map_err(|e| OpenNoteError::FailedToRun(e.to_string()))?;
+        run_editor(config, &path)?;
 
         let file = File::open(&path)?;
         let new_modified = file.metadata()?.modified()?;
 
         if old_modified != new_modified {
-            let folder = Folder::from_note(self);
-            println!();
-            print!(
-                "Updating {}... ",
-                format!("{}/{}", self.path, self.name).blue()
-            );
-            std::io::stdout().flush()?;
-
-            println!("{}", "Done".green());
-
-            if folder.sync_exists(config) {
-                print!("Syncing with remote...");
-                std::io::stdout().flush()?;
-            }
-
-            match folder.sync_note(self, config)? {
-                SyncStatus::Success => println!("{}", "Done".green()),
-                SyncStatus::Skipped => println!("{}", "Skipped".green()),
-                SyncStatus::NotSetup => {}
-            }
+            self.touch_updated()?;
+            self.finish_edit(config)?;
+        }
+
+        Ok(())
+    }
+
+    /// Decrypts the note into a private temporary file, opens that in the editor and encrypts
+    /// the result again if it changed. The temporary copy is shredded afterwards.
+    pub fn open_encrypted(
+        &self,
+        config: &Config,
+        passphrase: &SecretString,
+    ) -> Result<(), OpenNoteError> {
+        let content = self.decrypt_content(passphrase)?;
+
+        let directory = tempfile::Builder::new().prefix("nb-rs-").tempdir()?;
+        let path = directory.path().join(self.decrypted_name());
+        write_private(&path, &content)?;
+
+        let edited = run_editor(config, &path).and_then(|_| Ok(fs::read_to_string(&path)?));
+        // shred before looking at the result, so that a failed edit doesn't leave it behind
+        shred(&path)?;
+        let edited = edited?;
+
+        if edited != content {
+            let edited = touch_updated_content(&edited).unwrap_or(edited);
+            self.write_encrypted(&edited, passphrase)?;
+            self.finish_edit(config)?;
         }
 
         Ok(())
     }
+
+    fn finish_edit(&self, config: &Config) -> Result<(), OpenNoteError> {
+        let folder = Folder::from_note(self);
+        output::message("");
+        output::message_inline(&format!(
+            "Updating {}... ",
+            format!("{}/{}", self.path, self.name).blue()
+        ));
+
+        output::message(&"Done".green().to_string());
+
+        folder.sync_note(self, config)?;
+
+        Ok(())
+    }
+}
+
+/// Returns `content` with the `updated` front matter key set to the current time, or `None` if
+/// the note doesn't use it.
+fn touch_updated_content(content: &str) -> Option<String> {
+    let (format, raw, _) = split_front_matter(content)?;
+    let mut front_matter = FrontMatter::parse(format, raw).ok()?;
+
+    front_matter.updated()?;
+
+    front_matter.set("updated", &timestamp());
+    replace_front_matter(content, &front_matter).ok()
+}
+
+/// Opens `path` in the configured editor and waits until it's closed.
+pub fn run_editor(config: &Config, path: &Path) -> Result<(), OpenNoteError> {
+    let mut process = Command::new(&config.editor);
+    process.arg(path);
+
+    debug!(
+        "Executing {:?} with '{:?}'",
+        process.get_program(),
+        process.get_args()
+    );
+    process
+        .status()
+        .map_err(|e| OpenNoteError::FailedToRun(e.to_string()))?;
+
+    Ok(())
 }
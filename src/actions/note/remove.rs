@@ -1,14 +1,18 @@
-use std::io::Write;
-
 use colored::Colorize;
 use thiserror::Error;
 
 use crate::{
     actions::{
-        folder::{model::Folder, sync::sync_note::SyncError},
+        folder::{
+            index::IndexError,
+            model::Folder,
+            sync::{mutation::MutationAction, sync_note::SyncError},
+        },
         note::model::Note,
+        trash::model::{Trash, TrashError, TrashItem, TrashKind},
     },
     config::model::Config,
+    output,
 };
 
 #[derive(Error, Debug)]
@@ -21,10 +25,18 @@ pub enum RemoveNoteError {
 
     #[error(transparent)]
     Sync(#[from] SyncError),
+
+    #[error(transparent)]
+    Index(#[from] IndexError),
+
+    #[error(transparent)]
+    Trash(#[from] TrashError),
 }
 
 impl Note {
-    pub fn remove(&self, config: &Config) -> Result<(), RemoveNoteError> {
+    /// Moves the note to the trash after asking. Returns the trash item, or `None` if the user
+    /// changed their mind.
+    pub fn remove(&self, config: &Config) -> Result<Option<TrashItem>, RemoveNoteError> {
         let path = self.get_path();
 
         if !path.exists() {
@@ -33,29 +45,34 @@ impl Note {
 
         let mut input = String::new();
 
-        print!(
-            "Are you sure you want to delete '{}'? {}/{} ",
+        output::message_inline(&format!(
+            "Are you sure you want to move '{}' to the trash? {}/{} ",
             self.name.blue(),
             "y".red(),
             "N".green()
-        );
-
-        std::io::stdout().flush()?;
+        ));
 
         std::io::stdin().read_line(&mut input)?;
 
         if input.trim() != "y" {
-            println!("Aborting!");
-            return Ok(());
+            output::message("Aborting!");
+            return Ok(None);
         }
 
-        std::fs::remove_file(path)?;
-
         let folder = Folder::from_note(self);
-        folder.sync_note(self, config)?;
+        let id = folder.index_load()?.id_of(&self.name);
+
+        let item = Trash::new(&config.data_dir).put(&path, TrashKind::Note, id)?;
+        folder.index_remove(&self.name)?;
+
+        output::message(&format!(
+            "Moved {} to the trash as {}",
+            self.name.blue(),
+            item.id.yellow()
+        ));
 
-        println!("Removed {}", self.name.blue());
+        folder.sync_note_as(self, MutationAction::Remove, config)?;
 
-        Ok(())
+        Ok(Some(item))
     }
 }
@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+use thiserror::Error;
+
+use crate::{
+    actions::{
+        folder::{
+            index::IndexError,
+            links::{RewriteLinksError, normalize_path},
+            model::{Folder, FolderError},
+            sync::{
+                mutation::{Mutation, MutationAction},
+                sync_move::SyncMoveError,
+                sync_note::SyncError,
+            },
+        },
+        note::model::Note,
+    },
+    config::model::Config,
+    output,
+};
+
+#[derive(Error, Debug)]
+pub enum MoveNoteError {
+    #[error("the destination {0} already exists!")]
+    DestinationExists(String),
+
+    #[error("the folder {0} does not exist!")]
+    DestinationFolderMissing(String),
+
+    #[error("the destination {0} is outside of the data directory!")]
+    OutsideDataDir(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Folder(#[from] FolderError),
+
+    #[error(transparent)]
+    Index(#[from] IndexError),
+
+    #[error(transparent)]
+    Links(#[from] RewriteLinksError),
+
+    #[error(transparent)]
+    Move(#[from] SyncMoveError),
+
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+}
+
+type Error = MoveNoteError;
+
+impl Note {
+    /// Moves or renames the note. `destination` is relative to the data directory and may be a
+    /// folder, in which case the note keeps its file name.
+    pub fn move_to(&self, config: &Config, destination: &str) -> Result<(), Error> {
+        let data_dir = normalize_path(&config.data_dir);
+        let from = normalize_path(&self.get_path());
+
+        let mut to = normalize_path(&data_dir.join(destination));
+        if !to.starts_with(&data_dir) {
+            return Err(Error::OutsideDataDir(destination.to_string()));
+        }
+
+        if to.is_dir() {
+            to.push(&self.name);
+        } else if to.extension().is_none()
+            && let Some(extension) = from.extension()
+        {
+            to.set_extension(extension);
+        }
+
+        let display_name = |path: &PathBuf| {
+            path.strip_prefix(&data_dir)
+                .unwrap_or(path)
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+
+        if to.exists() {
+            return Err(Error::DestinationExists(display_name(&to)));
+        }
+
+        let parent = to.parent().unwrap().to_path_buf();
+        if !parent.is_dir() {
+            return Err(Error::DestinationFolderMissing(display_name(&parent)));
+        }
+
+        let new_name = to.file_name().unwrap().to_str().unwrap().to_string();
+
+        let source_folder = Folder::from_note(self);
+        let destination_folder = Folder::from_pathbuf(&parent, ".")?;
+
+        let git_root = source_folder.sync_move(&destination_folder, &from, &to, config)?;
+
+        if normalize_path(&source_folder.get_path()) == parent {
+            source_folder.index_rename(&self.name, &new_name)?;
+        } else {
+            source_folder.index_remove(&self.name)?;
+            destination_folder.index_add(&new_name)?;
+        }
+
+        let toplevel = Folder::from_pathbuf(&config.data_dir, ".")?;
+        let mut changed = toplevel.rewrite_links(&from, &to)?;
+
+        output::message(&format!(
+            "Moved '{}' to '{}'",
+            display_name(&from).blue(),
+            display_name(&to).blue()
+        ));
+
+        if !changed.is_empty() {
+            output::message(&format!("Updated links in {} notes", changed.len()));
+        }
+
+        let git_root = match git_root {
+            None => return Ok(()),
+            Some(value) => value,
+        };
+
+        // the destination goes first, it decides which repository records the move
+        changed.insert(0, to.clone());
+        changed.push(source_folder.index_path());
+        changed.push(destination_folder.index_path());
+        changed.retain(|path| path.exists() && path.starts_with(git_root.get_path()));
+
+        Mutation::new(
+            MutationAction::Move,
+            format!("{} -> {}", display_name(&from), display_name(&to)),
+            changed,
+        )
+        .commit(config)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeSet;
+
+use crate::actions::note::{
+    front_matter::{FrontMatter, split_front_matter},
+    model::{Note, NoteError},
+};
+
+/// Brings a tag into its canonical form: without the leading `#` or surrounding slashes and in
+/// lowercase. Purely numeric tags like `#123` are usually issue numbers and are ignored.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().trim_start_matches('#').trim_matches('/');
+
+    if tag.is_empty() || tag.chars().all(|c| c.is_ascii_digit() || c == '/') {
+        return None;
+    }
+
+    Some(tag.to_lowercase())
+}
+
+/// Returns true if `tag` is `filter` or one of its children, so `work` matches `work/meetings`.
+pub fn tag_matches(tag: &str, filter: &str) -> bool {
+    tag == filter
+        || tag
+            .strip_prefix(filter)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '/'
+}
+
+pub fn is_heading(line: &str) -> bool {
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    (1..=6).contains(&hashes)
+        && line[hashes..]
+            .chars()
+            .next()
+            .is_none_or(|c| c == ' ' || c == '\t')
+}
+
+/// Collects inline `#tag` and `#parent/child` tokens. Headings, fenced code blocks and inline
+/// code are skipped.
+pub fn parse_inline_tags(body: &str) -> BTreeSet<String> {
+    let mut tags = BTreeSet::new();
+    let mut fence: Option<&str> = None;
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+
+        if let Some(current) = fence {
+            if trimmed.starts_with(current) {
+                fence = None;
+            }
+            continue;
+        }
+
+        if trimmed.starts_with("```") {
+            fence = Some("```");
+            continue;
+        }
+
+        if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+            continue;
+        }
+
+        if is_heading(trimmed) {
+            continue;
+        }
+
+        let mut in_code = false;
+        let mut previous: Option<char> = None;
+
+        for (index, c) in line.char_indices() {
+            if c == '`' {
+                in_code = !in_code;
+            } else if c == '#' && !in_code && previous.is_none_or(|c| c.is_whitespace()) {
+                let rest = &line[index + 1..];
+                let end = rest.find(|c| !is_tag_char(c)).unwrap_or(rest.len());
+
+                if let Some(tag) = normalize_tag(&rest[..end]) {
+                    tags.insert(tag);
+                }
+            }
+
+            previous = Some(c);
+        }
+    }
+
+    tags
+}
+
+/// Returns the tags from the front matter and the inline `#tags` of the body.
+pub fn parse_tags(content: &str) -> BTreeSet<String> {
+    let (mut tags, body) = match split_front_matter(content) {
+        None => (BTreeSet::new(), content),
+        Some((format, raw, body)) => (
+            FrontMatter::parse(format, raw)
+                .map(|front_matter| front_matter.tags())
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|tag| normalize_tag(tag))
+                .collect(),
+            body,
+        ),
+    };
+
+    tags.append(&mut parse_inline_tags(body));
+
+    tags
+}
+
+impl Note {
+    /// Returns the tags from the front matter and the inline `#tags` of the body.
+    pub fn get_tags(&self) -> Result<BTreeSet<String>, NoteError> {
+        Ok(parse_tags(&self.get_content()?))
+    }
+
+    /// Returns true if the note has all of the given tags (or children of them).
+    pub fn has_tags(&self, filters: &[String]) -> Result<bool, NoteError> {
+        if filters.is_empty() {
+            return Ok(true);
+        }
+
+        let tags = self.get_tags()?;
+
+        Ok(filters
+            .iter()
+            .filter_map(|f| normalize_tag(f))
+            .all(|filter| tags.iter().any(|tag| tag_matches(tag, &filter))))
+    }
+}
@@ -0,0 +1,223 @@
+use std::fs;
+
+use chrono::NaiveDate;
+use thiserror::Error;
+
+use crate::{
+    actions::{
+        folder::{model::Folder, sync::sync_note::SyncError},
+        note::{
+            model::{Note, NoteError},
+            todo::checkbox_label,
+        },
+    },
+    config::model::Config,
+    output,
+};
+
+#[derive(Error, Debug)]
+pub enum TaskError {
+    #[error("line {1} of '{0}' is not a task")]
+    NotATask(String, usize),
+
+    #[error("'{0}' is not a task, expected the note and line like work/3:12")]
+    InvalidSelector(String),
+
+    #[error("the note {0} is encrypted, open it to edit it")]
+    Encrypted(String),
+
+    #[error(transparent)]
+    Note(#[from] NoteError),
+
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+type Error = TaskError;
+
+/// A markdown checkbox item like `- [ ] send the report @alice @due(2026-01-31)`.
+#[derive(Debug, Clone)]
+pub struct Task {
+    /// The line of the item in the note, counted from 1
+    pub line: usize,
+    pub text: String,
+    pub done: bool,
+    /// From `@due(2026-01-31)` or `due:2026-01-31`
+    pub due: Option<NaiveDate>,
+    /// Everyone mentioned with `@name`
+    pub owners: Vec<String>,
+}
+
+/// Splits a task selector like `work/3:12` into the note selector and the line.
+pub fn parse_task_selector(selector: &str) -> Result<(&str, usize), Error> {
+    selector
+        .rsplit_once(':')
+        .and_then(|(note, line)| Some((note, line.parse().ok()?)))
+        .filter(|(note, line)| !note.is_empty() && *line > 0)
+        .ok_or_else(|| Error::InvalidSelector(selector.to_string()))
+}
+
+/// Splits a checkbox list item into the part before the box, its state and the text after it.
+pub fn parse_checkbox(line: &str) -> Option<(&str, bool, &str)> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+
+    let marker_length =
+        if trimmed.starts_with("- ") || trimmed.starts_with("* ") || trimmed.starts_with("+ ") {
+            2
+        } else {
+            let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+            let rest = &trimmed[digits..];
+            match digits > 0 && (rest.starts_with(". ") || rest.starts_with(") ")) {
+                true => digits + 2,
+                false => return None,
+            }
+        };
+
+    let rest = &trimmed[marker_length..];
+    let done = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+
+    let text = &rest[3..];
+    if !text.is_empty() && !text.starts_with(' ') {
+        return None;
+    }
+
+    Some((&line[..indent + marker_length], done, text.trim()))
+}
+
+fn parse_due(text: &str) -> Option<NaiveDate> {
+    let value = match text.find("@due(") {
+        Some(start) => {
+            let rest = &text[start + "@due(".len()..];
+            &rest[..rest.find(')')?]
+        }
+        None => {
+            let start = text
+                .match_indices("due:")
+                .find(|(index, _)| *index == 0 || text[..*index].ends_with(' '))?
+                .0;
+            text[start + "due:".len()..].split_whitespace().next()?
+        }
+    };
+
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok()
+}
+
+fn parse_owners(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .filter_map(|word| word.strip_prefix('@'))
+        .filter(|word| !word.starts_with("due("))
+        .map(|word| word.trim_end_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// Collects all checkbox items of a note. Items in fenced code blocks are skipped.
+pub fn parse_tasks(content: &str) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    let mut fence: Option<&str> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+
+        if let Some(current) = fence {
+            if trimmed.starts_with(current) {
+                fence = None;
+            }
+            continue;
+        }
+
+        if trimmed.starts_with("```") {
+            fence = Some("```");
+            continue;
+        }
+
+        if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+            continue;
+        }
+
+        if let Some((_, done, text)) = parse_checkbox(line) {
+            tasks.push(Task {
+                line: index + 1,
+                text: text.to_string(),
+                done,
+                due: parse_due(text),
+                owners: parse_owners(text),
+            });
+        }
+    }
+
+    tasks
+}
+
+impl Task {
+    /// Formats the task as `[ ] text (due 2026-01-31)`.
+    pub fn label(&self) -> String {
+        checkbox_label(self.done, &self.text, self.due)
+    }
+}
+
+impl Note {
+    /// Returns the checkbox items of the note. Encrypted notes have none as far as we know.
+    pub fn get_tasks(&self) -> Result<Vec<Task>, NoteError> {
+        if self.is_encrypted() {
+            return Ok(Vec::new());
+        }
+
+        Ok(parse_tasks(&self.get_content()?))
+    }
+
+    /// Checks or unchecks the item on `line` in place and commits the change.
+    pub fn set_task_done(&self, config: &Config, line: usize, done: bool) -> Result<Task, Error> {
+        if self.is_encrypted() {
+            return Err(Error::Encrypted(self.name.clone()));
+        }
+
+        let content = self.get_content()?;
+
+        // also makes sure that the line isn't in a code block
+        let mut task = parse_tasks(&content)
+            .into_iter()
+            .find(|task| task.line == line)
+            .ok_or(Error::NotATask(self.name.clone(), line))?;
+
+        let mut lines: Vec<&str> = content.split_inclusive('\n').collect();
+        let current = lines[line - 1];
+
+        let (prefix, _, text) = parse_checkbox(current.trim_end_matches(['\n', '\r']))
+            .ok_or(Error::NotATask(self.name.clone(), line))?;
+
+        let ending = &current[current.trim_end_matches(['\n', '\r']).len()..];
+        let replaced = format!(
+            "{}[{}]{}{}{}",
+            prefix,
+            if done { "x" } else { " " },
+            if text.is_empty() { "" } else { " " },
+            text,
+            ending
+        );
+        lines[line - 1] = &replaced;
+
+        fs::write(self.get_path(), lines.concat())?;
+        task.done = done;
+
+        output::message(&format!(
+            "{} {}",
+            if done { "Done:" } else { "Reopened:" },
+            task.label()
+        ));
+
+        Folder::from_note(self).sync_note(self, config)?;
+
+        Ok(task)
+    }
+}
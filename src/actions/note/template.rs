@@ -0,0 +1,227 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{Local, NaiveDate, NaiveTime};
+use colored::Colorize;
+use thiserror::Error;
+use tracing::debug;
+
+use crate::{
+    actions::{
+        folder::{
+            model::Folder,
+            sync::{
+                mutation::{Mutation, MutationAction},
+                sync_note::SyncError,
+            },
+        },
+        note::front_matter::{FrontMatter, FrontMatterError, split_front_matter},
+    },
+    config::model::Config,
+    output,
+};
+
+/// Hidden folder in the data dir that holds the templates, e.g. `.templates/meeting.md`.
+pub const TEMPLATES_FOLDER: &str = ".templates";
+
+/// A folder that contains this file creates new notes from the template named in it.
+pub const FOLDER_TEMPLATE_FILE: &str = ".template";
+
+#[derive(Error, Debug)]
+pub enum TemplateError {
+    #[error("the template {0} does not exist in .templates")]
+    NotFound(String),
+
+    #[error("invalid template variable '{0}', expected key=value")]
+    InvalidVariable(String),
+
+    #[error("unclosed '{{{{' in the template {0}")]
+    Unclosed(String),
+
+    #[error(transparent)]
+    FrontMatter(#[from] FrontMatterError),
+
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+type Error = TemplateError;
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub name: String,
+    pub content: String,
+}
+
+/// The values of the built-in variables `{{title}}`, `{{date}}`, `{{time}}`, `{{folder}}` and
+/// `{{uuid}}`. Anything else is taken from `variables` or asked for.
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    pub title: String,
+    pub folder: String,
+    pub date: NaiveDate,
+    pub time: NaiveTime,
+    pub variables: HashMap<String, String>,
+}
+
+/// Parses a `key=value` pair given with `--var`.
+pub fn parse_variable(value: &str) -> Result<(String, String), Error> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(Error::InvalidVariable(value.to_string())),
+    }
+}
+
+fn prompt_variable(name: &str) -> Result<String, Error> {
+    output::message_inline(&format!("Value for {}: ", name.blue()));
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+
+    Ok(input.trim_end_matches(['\n', '\r']).to_string())
+}
+
+impl TemplateContext {
+    pub fn new(title: impl ToString, folder: impl ToString) -> Self {
+        let now = Local::now();
+
+        Self {
+            title: title.to_string(),
+            folder: folder.to_string(),
+            date: now.date_naive(),
+            time: now.time(),
+            variables: HashMap::new(),
+        }
+    }
+
+    fn builtin(&self, name: &str) -> Option<String> {
+        match name {
+            "title" => Some(self.title.clone()),
+            "date" => Some(self.date.format("%Y-%m-%d").to_string()),
+            "time" => Some(self.time.format("%H:%M").to_string()),
+            "folder" => Some(self.folder.clone()),
+            "uuid" => Some(uuid::Uuid::new_v4().to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl Template {
+    /// Loads `.templates/<name>`, falling back to `.templates/<name>.md`.
+    pub fn load(data_dir: &Path, name: &str) -> Result<Self, Error> {
+        let folder = data_dir.join(TEMPLATES_FOLDER);
+
+        let path = [folder.join(name), folder.join(format!("{}.md", name))]
+            .into_iter()
+            .find(|path| path.is_file())
+            .ok_or_else(|| Error::NotFound(name.to_string()))?;
+
+        debug!("Using template '{}'", path.to_str().unwrap());
+
+        Ok(Self {
+            name: name.to_string(),
+            content: fs::read_to_string(path)?,
+        })
+    }
+
+    /// Like [`Template::load`], but a missing template is not an error.
+    pub fn load_optional(data_dir: &Path, name: &str) -> Result<Option<Self>, Error> {
+        match Self::load(data_dir, name) {
+            Ok(template) => Ok(Some(template)),
+            Err(Error::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fills in all `{{variables}}`. Unknown variables that weren't passed in the context are
+    /// asked for once each.
+    pub fn render(&self, context: &TemplateContext) -> Result<String, Error> {
+        let mut rendered = String::with_capacity(self.content.len());
+        let mut prompted: HashMap<String, String> = HashMap::new();
+        let mut rest = self.content.as_str();
+
+        while let Some(start) = rest.find("{{") {
+            rendered.push_str(&rest[..start]);
+
+            let end = rest[start..]
+                .find("}}")
+                .ok_or_else(|| Error::Unclosed(self.name.clone()))?;
+            let name = rest[start + 2..start + end].trim();
+
+            let value = match context
+                .builtin(name)
+                .or_else(|| context.variables.get(name).cloned())
+            {
+                Some(value) => value,
+                None => match prompted.get(name) {
+                    Some(value) => value.clone(),
+                    None => {
+                        let value = prompt_variable(name)?;
+                        prompted.insert(name.to_string(), value.clone());
+                        value
+                    }
+                },
+            };
+
+            rendered.push_str(&value);
+            rest = &rest[start + end + 2..];
+        }
+
+        rendered.push_str(rest);
+
+        Ok(rendered)
+    }
+}
+
+/// Splits rendered template content into its front matter, if any, and the body.
+pub fn split_rendered(content: &str) -> Result<(Option<FrontMatter>, String), Error> {
+    match split_front_matter(content) {
+        None => Ok((None, content.to_string())),
+        Some((format, raw, body)) => Ok((Some(FrontMatter::parse(format, raw)?), body.to_string())),
+    }
+}
+
+impl Folder {
+    fn template_file(&self) -> PathBuf {
+        self.get_path().join(FOLDER_TEMPLATE_FILE)
+    }
+
+    /// The name of the template new notes in this folder start from, if it declares one.
+    pub fn default_template(&self) -> Result<Option<String>, Error> {
+        let path = self.template_file();
+
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let name = fs::read_to_string(path)?.trim().to_string();
+        Ok((!name.is_empty()).then_some(name))
+    }
+
+    /// Makes `template` the default for new notes in this folder and commits that.
+    pub fn set_default_template(&self, config: &Config, template: &str) -> Result<(), Error> {
+        // fail early instead of on the next note
+        Template::load(&config.data_dir, template)?;
+
+        let path = self.template_file();
+        fs::write(&path, format!("{}\n", template))?;
+
+        output::message(&format!(
+            "New notes in '{}' start from the template '{}'",
+            self.name.blue(),
+            template.blue()
+        ));
+
+        Mutation::new(MutationAction::Template, &self.name, vec![path]).commit(config)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use std::fs;
+
+use chrono::{Local, NaiveDate};
+use colored::Colorize;
+use thiserror::Error;
+
+use crate::{
+    actions::{
+        folder::{model::Folder, sync::sync_note::SyncError},
+        note::model::{Note, NoteError},
+    },
+    config::model::Config,
+    output,
+};
+
+/// Todo notes end in this, the same way nb names them.
+pub const TODO_SUFFIX: &str = ".todo.md";
+
+#[derive(Error, Debug)]
+pub enum TodoError {
+    #[error("'{0}' is not a todo")]
+    NotATodo(String),
+
+    #[error(transparent)]
+    Note(#[from] NoteError),
+
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+type Error = TodoError;
+
+/// The state of a todo note, read from its `# [ ] title` heading and `## Due` section.
+#[derive(Debug, Clone)]
+pub struct Todo {
+    pub title: String,
+    pub done: bool,
+    pub due: Option<NaiveDate>,
+}
+
+/// Parses a checkbox heading like `# [x] title`. Returns whether it's checked and the title.
+fn parse_todo_heading(line: &str) -> Option<(bool, &str)> {
+    let rest = line.strip_prefix("# [")?;
+    let (state, title) = rest.split_once(']')?;
+
+    let done = match state {
+        " " => false,
+        "x" | "X" => true,
+        _ => return None,
+    };
+
+    Some((done, title.trim()))
+}
+
+impl Todo {
+    pub fn parse(content: &str) -> Option<Self> {
+        let mut lines = content.lines().skip_while(|line| line.trim().is_empty());
+        let (done, title) = parse_todo_heading(lines.next()?)?;
+
+        let mut due = None;
+        let mut in_due = false;
+
+        for line in lines {
+            let line = line.trim();
+
+            if line.starts_with('#') {
+                in_due = line == "## Due";
+            } else if in_due && !line.is_empty() {
+                due = NaiveDate::parse_from_str(line, "%Y-%m-%d").ok();
+                in_due = false;
+            }
+        }
+
+        Some(Self {
+            title: title.to_string(),
+            done,
+            due,
+        })
+    }
+
+    /// The content of a new todo note.
+    pub fn serialize_new(title: &str, due: Option<NaiveDate>) -> String {
+        let mut content = format!("# [ ] {}\n", title);
+
+        if let Some(due) = due {
+            content.push_str(&format!("\n## Due\n\n{}\n", due.format("%Y-%m-%d")));
+        }
+
+        content
+    }
+
+    /// Formats the todo as `[ ] title (due 2026-01-31)`.
+    pub fn label(&self) -> String {
+        checkbox_label(self.done, &self.title, self.due)
+    }
+}
+
+/// Formats a todo or task as `[ ] text (due 2026-01-31)`, with the due date in red once it's
+/// overdue.
+pub fn checkbox_label(done: bool, text: &str, due: Option<NaiveDate>) -> String {
+    let checkbox = match done {
+        true => "[x]".green(),
+        false => "[ ]".yellow(),
+    };
+
+    let mut label = format!("{} {}", checkbox, text);
+
+    if let Some(due) = due {
+        let overdue = !done && due < Local::now().date_naive();
+        let due = format!("(due {})", due.format("%Y-%m-%d"));
+        label.push_str(&format!(
+            " {}",
+            if overdue { due.red() } else { due.dimmed() }
+        ));
+    }
+
+    label
+}
+
+impl Note {
+    pub fn is_todo(&self) -> bool {
+        self.name.ends_with(TODO_SUFFIX)
+    }
+
+    /// Returns the todo state of the note, or `None` if it isn't a todo.
+    pub fn get_todo(&self) -> Result<Option<Todo>, NoteError> {
+        if !self.is_todo() {
+            return Ok(None);
+        }
+
+        Ok(Todo::parse(&self.get_content()?))
+    }
+
+    /// Checks or unchecks the heading of a todo note and commits the change.
+    pub fn set_todo_done(&self, config: &Config, done: bool) -> Result<Todo, Error> {
+        let content = self.get_content()?;
+
+        let mut todo = match self.is_todo().then(|| Todo::parse(&content)).flatten() {
+            None => return Err(Error::NotATodo(self.name.clone())),
+            Some(value) => value,
+        };
+
+        let mut replaced = false;
+        let new_content: Vec<String> = content
+            .split_inclusive('\n')
+            .map(|line| {
+                if !replaced && parse_todo_heading(line.trim_end()).is_some() {
+                    replaced = true;
+                    let ending = &line[line.trim_end().len()..];
+                    format!(
+                        "# [{}] {}{}",
+                        if done { "x" } else { " " },
+                        todo.title,
+                        ending
+                    )
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect();
+
+        fs::write(self.get_path(), new_content.concat())?;
+        todo.done = done;
+
+        output::message(&format!(
+            "{} {}",
+            if done { "Done:" } else { "Reopened:" },
+            todo.label()
+        ));
+
+        Folder::from_note(self).sync_note(self, config)?;
+
+        Ok(todo)
+    }
+}
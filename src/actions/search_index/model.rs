@@ -0,0 +1,253 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::debug;
+
+use crate::actions::{
+    folder::{list::ListFolderError, model::FolderError},
+    note::{
+        model::{Note, NoteError},
+        tags::{normalize_tag, tag_matches},
+    },
+};
+
+#[derive(Error, Debug)]
+pub enum SearchIndexError {
+    #[error("failed to access the search index: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to write the search index: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    ListFolder(#[from] ListFolderError),
+
+    #[error(transparent)]
+    Note(#[from] NoteError),
+
+    #[error(transparent)]
+    Folder(#[from] FolderError),
+}
+
+/// The hidden folder in the data directory that holds the search index.
+pub const SEARCH_INDEX_DIR: &str = ".cache";
+pub const SEARCH_INDEX_FILE: &str = "search-index.json";
+
+/// Bump this whenever the format changes, older indexes are rebuilt automatically.
+const SEARCH_INDEX_VERSION: u32 = 3;
+
+/// Everything the index knows about a single note.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct IndexedNote {
+    /// Modification time in nanoseconds since the epoch, used to detect changes
+    pub modified: u64,
+    pub size: u64,
+    pub title: Option<String>,
+    /// The `created` key of the front matter, as written there
+    pub created: Option<String>,
+    pub aliases: Vec<String>,
+    pub tags: BTreeSet<String>,
+    pub headings: Vec<String>,
+    /// Number of terms in the note
+    pub length: usize,
+}
+
+/// An inverted index over all notes in the data directory. Notes are keyed by their path relative
+/// to the data directory.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SearchIndex {
+    version: u32,
+
+    pub notes: BTreeMap<String, IndexedNote>,
+
+    /// Maps every term to the notes containing it and how often it appears there
+    pub terms: BTreeMap<String, BTreeMap<String, u32>>,
+
+    #[serde(skip)]
+    pub(super) root: PathBuf,
+
+    #[serde(skip)]
+    pub(super) dirty: bool,
+}
+
+/// Splits text into lowercase alphanumeric terms.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| term.to_lowercase())
+}
+
+impl SearchIndex {
+    pub fn path(root: &Path) -> PathBuf {
+        let mut path = root.to_path_buf();
+        path.push(SEARCH_INDEX_DIR);
+        path.push(SEARCH_INDEX_FILE);
+        path
+    }
+
+    /// Loads the index from disk. A missing, outdated or broken index results in an empty one
+    /// that gets filled by the next refresh.
+    pub fn load(root: &Path) -> Self {
+        let path = Self::path(root);
+
+        let index = fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice::<SearchIndex>(&content).ok())
+            .filter(|index| index.version == SEARCH_INDEX_VERSION);
+
+        let mut index = match index {
+            Some(index) => index,
+            None => {
+                debug!(
+                    "No usable search index at {:?}, starting from scratch",
+                    path
+                );
+                SearchIndex {
+                    version: SEARCH_INDEX_VERSION,
+                    dirty: true,
+                    ..Default::default()
+                }
+            }
+        };
+
+        index.root = root.to_path_buf();
+        index
+    }
+
+    /// Writes the index if anything changed since it was loaded.
+    pub fn save(&mut self) -> Result<(), SearchIndexError> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let path = Self::path(&self.root);
+        fs::create_dir_all(path.parent().unwrap())?;
+
+        // write to a temporary file first, so that an interrupted write can't corrupt the index
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec(self)?)?;
+        fs::rename(tmp_path, path)?;
+
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    /// Returns the key of a note, which is its path relative to the data directory.
+    pub fn key_of(&self, note: &Note) -> Option<String> {
+        note.get_path()
+            .strip_prefix(&self.root)
+            .ok()?
+            .to_str()
+            .map(|key| key.to_string())
+    }
+
+    pub fn get(&self, note: &Note) -> Option<&IndexedNote> {
+        self.notes.get(&self.key_of(note)?)
+    }
+
+    /// Returns the title of a note, reading the note itself if it isn't indexed.
+    pub fn title_of(&self, note: &Note) -> Result<Option<String>, NoteError> {
+        match self.get(note) {
+            Some(indexed) => Ok(indexed.title.clone()),
+            None => note.get_pretty_name(),
+        }
+    }
+
+    /// Like [`Note::get_name`], but served from the index.
+    pub fn name_of(&self, note: &Note) -> Result<String, NoteError> {
+        Ok(self.title_of(note)?.unwrap_or(note.name.clone()))
+    }
+
+    /// Returns the `created` front matter key of a note, reading the note itself if it isn't
+    /// indexed.
+    pub fn created_of(&self, note: &Note) -> Result<Option<String>, NoteError> {
+        match self.get(note) {
+            Some(indexed) => Ok(indexed.created.clone()),
+            None => Ok(note
+                .get_front_matter()?
+                .and_then(|front_matter| front_matter.created())),
+        }
+    }
+
+    pub fn tags_of(&self, note: &Note) -> Result<BTreeSet<String>, NoteError> {
+        match self.get(note) {
+            Some(indexed) => Ok(indexed.tags.clone()),
+            None => note.get_tags(),
+        }
+    }
+
+    /// Like [`Note::has_tags`], but served from the index.
+    pub fn has_tags(&self, note: &Note, filters: &[String]) -> Result<bool, NoteError> {
+        if filters.is_empty() {
+            return Ok(true);
+        }
+
+        let indexed = match self.get(note) {
+            None => return note.has_tags(filters),
+            Some(value) => value,
+        };
+
+        Ok(filters
+            .iter()
+            .filter_map(|filter| normalize_tag(filter))
+            .all(|filter| indexed.tags.iter().any(|tag| tag_matches(tag, &filter))))
+    }
+
+    /// Like [`Note::matches_name`], but served from the index.
+    pub fn matches_name(&self, note: &Note, name: &str) -> Result<bool, NoteError> {
+        let indexed = match self.get(note) {
+            None => return note.matches_name(name),
+            Some(value) => value,
+        };
+
+        let title = indexed.title.as_deref().unwrap_or(&note.name);
+
+        Ok(title.to_lowercase().contains(name)
+            || indexed
+                .aliases
+                .iter()
+                .any(|alias| alias.to_lowercase().contains(name)))
+    }
+
+    /// Returns the keys of all notes that may contain `query` as a substring, or `None` if the
+    /// query has no terms to narrow the search down with.
+    pub fn candidates(&self, query: &str) -> Option<BTreeSet<String>> {
+        let mut result: Option<BTreeSet<String>> = None;
+
+        for query_term in tokenize(query) {
+            let mut notes = BTreeSet::new();
+
+            for (term, postings) in self.terms.iter() {
+                if term.contains(&query_term) {
+                    notes.extend(postings.keys().cloned());
+                }
+            }
+
+            result = Some(match result {
+                None => notes,
+                Some(previous) => previous.intersection(&notes).cloned().collect(),
+            });
+        }
+
+        result
+    }
+
+    /// Returns true if `note` can be skipped because the index knows it doesn't match.
+    pub fn can_skip(&self, note: &Note, candidates: &Option<BTreeSet<String>>) -> bool {
+        let candidates = match candidates {
+            None => return false,
+            Some(value) => value,
+        };
+
+        match self.key_of(note) {
+            Some(key) => self.notes.contains_key(&key) && !candidates.contains(&key),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+
+use crate::actions::{
+    note::model::Note,
+    search_index::model::{SearchIndex, tokenize},
+};
+
+/// BM25 term frequency saturation.
+const K1: f64 = 1.2;
+
+/// BM25 document length normalization.
+const B: f64 = 0.75;
+
+/// Extra weight for a query term that appears in the title, on top of its body score.
+const TITLE_BOOST: f64 = 2.0;
+
+/// Extra weight for a query term that appears in one of the headings.
+const HEADING_BOOST: f64 = 1.0;
+
+struct RankedTerm {
+    term: String,
+    idf: f64,
+    /// How often the term appears in each note, keyed like [`SearchIndex::notes`]
+    frequencies: BTreeMap<String, u32>,
+}
+
+/// Scores notes against the terms of a query with BM25. Like the candidate lookup, a query term
+/// matches every index term that contains it, so `deploy` also counts `deployment`.
+pub struct Ranker<'a> {
+    index: &'a SearchIndex,
+    terms: Vec<RankedTerm>,
+    average_length: f64,
+}
+
+impl SearchIndex {
+    pub fn ranker(&self, query_terms: &[String]) -> Ranker<'_> {
+        let note_count = self.notes.len() as f64;
+
+        let terms = query_terms
+            .iter()
+            .map(|query_term| {
+                let mut frequencies: BTreeMap<String, u32> = BTreeMap::new();
+
+                for (term, postings) in self.terms.iter() {
+                    if term.contains(query_term.as_str()) {
+                        for (key, count) in postings.iter() {
+                            *frequencies.entry(key.clone()).or_insert(0) += count;
+                        }
+                    }
+                }
+
+                let document_count = frequencies.len() as f64;
+                let idf = ((note_count - document_count + 0.5) / (document_count + 0.5) + 1.0).ln();
+
+                RankedTerm {
+                    term: query_term.clone(),
+                    idf,
+                    frequencies,
+                }
+            })
+            .collect();
+
+        let total_length: usize = self.notes.values().map(|note| note.length).sum();
+
+        Ranker {
+            index: self,
+            terms,
+            average_length: match self.notes.is_empty() {
+                true => 0.0,
+                false => total_length as f64 / note_count,
+            },
+        }
+    }
+}
+
+fn contains_term(text: &str, query_term: &str) -> bool {
+    tokenize(text).any(|term| term.contains(query_term))
+}
+
+impl Ranker<'_> {
+    /// Returns false if the query has no terms to rank by, e.g. because it's a regular expression.
+    pub fn has_terms(&self) -> bool {
+        !self.terms.is_empty()
+    }
+
+    /// Returns the relevance of `note`. Notes the index doesn't know about score 0.
+    pub fn score(&self, note: &Note) -> f64 {
+        let (key, indexed) = match self
+            .index
+            .key_of(note)
+            .and_then(|key| self.index.notes.get(&key).map(|indexed| (key, indexed)))
+        {
+            None => return 0.0,
+            Some(value) => value,
+        };
+
+        let length_ratio = match self.average_length > 0.0 {
+            true => indexed.length as f64 / self.average_length,
+            false => 1.0,
+        };
+
+        let mut score = 0.0;
+
+        for term in self.terms.iter() {
+            let frequency = term.frequencies.get(&key).copied().unwrap_or(0) as f64;
+            score += term.idf * (frequency * (K1 + 1.0))
+                / (frequency + K1 * (1.0 - B + B * length_ratio));
+
+            if indexed
+                .title
+                .as_deref()
+                .is_some_and(|title| contains_term(title, &term.term))
+            {
+                score += term.idf * TITLE_BOOST;
+            }
+
+            if indexed
+                .headings
+                .iter()
+                .any(|heading| contains_term(heading, &term.term))
+            {
+                score += term.idf * HEADING_BOOST;
+            }
+        }
+
+        score
+    }
+}
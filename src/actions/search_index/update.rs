@@ -0,0 +1,220 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+    time::UNIX_EPOCH,
+};
+
+use tracing::debug;
+
+use crate::actions::{
+    folder::model::Folder,
+    note::{
+        front_matter::{FrontMatter, split_front_matter},
+        model::{Note, parse_headings, parse_pretty_name},
+        tags::parse_tags,
+    },
+    search_index::model::{IndexedNote, SearchIndex, SearchIndexError, tokenize},
+};
+
+type Error = SearchIndexError;
+
+/// How the notes on disk differ from the index.
+#[derive(Default, Debug)]
+pub struct IndexChanges {
+    pub added: usize,
+    pub changed: usize,
+    pub removed: usize,
+}
+
+impl IndexChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.changed == 0 && self.removed == 0
+    }
+}
+
+fn modified_and_size(path: &Path) -> Result<(u64, u64), Error> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(0);
+
+    Ok((modified, metadata.len()))
+}
+
+impl SearchIndex {
+    /// Loads the index and brings it up to date with the notes on disk.
+    pub fn open(root: &Path) -> Result<Self, Error> {
+        let mut index = Self::load(root);
+        index.refresh()?;
+        index.save()?;
+        Ok(index)
+    }
+
+    /// Collects every note below the data directory, using the same rules as `ls`.
+    fn collect_notes(folder: &Folder, notes: &mut Vec<Note>) -> Result<(), Error> {
+        let entries = folder.list()?;
+        notes.extend(entries.notes);
+
+        for folder in entries.folders.iter() {
+            Self::collect_notes(folder, notes)?;
+        }
+
+        Ok(())
+    }
+
+    fn current_notes(&self) -> Result<BTreeMap<String, (Note, u64, u64)>, Error> {
+        let mut notes = Vec::new();
+        Self::collect_notes(&Folder::from_pathbuf(&self.root, ".")?, &mut notes)?;
+
+        let mut current = BTreeMap::new();
+        for note in notes {
+            let key = match self.key_of(&note) {
+                None => continue,
+                Some(value) => value,
+            };
+            let (modified, size) = modified_and_size(&note.get_path())?;
+            current.insert(key, (note, modified, size));
+        }
+
+        Ok(current)
+    }
+
+    /// Compares the index with the notes on disk without changing anything.
+    pub fn status(&self) -> Result<IndexChanges, Error> {
+        let current = self.current_notes()?;
+        let mut changes = IndexChanges::default();
+
+        for (key, (_, modified, size)) in current.iter() {
+            match self.notes.get(key) {
+                None => changes.added += 1,
+                Some(indexed) if indexed.modified != *modified || indexed.size != *size => {
+                    changes.changed += 1
+                }
+                Some(_) => {}
+            }
+        }
+
+        changes.removed = self
+            .notes
+            .keys()
+            .filter(|key| !current.contains_key(*key))
+            .count();
+
+        Ok(changes)
+    }
+
+    /// Re-indexes every note whose modification time or size changed and drops deleted notes.
+    pub fn refresh(&mut self) -> Result<IndexChanges, Error> {
+        let current = self.current_notes()?;
+        let mut changes = IndexChanges::default();
+
+        let removed: Vec<String> = self
+            .notes
+            .keys()
+            .filter(|key| !current.contains_key(*key))
+            .cloned()
+            .collect();
+
+        for key in removed {
+            debug!("Removing {} from the search index", key);
+            self.remove_note(&key);
+            changes.removed += 1;
+        }
+
+        for (key, (note, modified, size)) in current {
+            match self.notes.get(&key) {
+                Some(indexed) if indexed.modified == modified && indexed.size == size => continue,
+                Some(_) => changes.changed += 1,
+                None => changes.added += 1,
+            }
+
+            debug!("Indexing {}", key);
+            self.index_note(key, &note, modified, size)?;
+        }
+
+        Ok(changes)
+    }
+
+    /// Throws the index away and indexes every note again.
+    pub fn rebuild(&mut self) -> Result<IndexChanges, Error> {
+        self.notes.clear();
+        self.terms.clear();
+        self.dirty = true;
+
+        self.refresh()
+    }
+
+    fn remove_note(&mut self, key: &str) {
+        self.notes.remove(key);
+
+        self.terms.retain(|_, postings| {
+            postings.remove(key);
+            !postings.is_empty()
+        });
+
+        self.dirty = true;
+    }
+
+    fn index_note(
+        &mut self,
+        key: String,
+        note: &Note,
+        modified: u64,
+        size: u64,
+    ) -> Result<(), Error> {
+        if self.notes.contains_key(&key) {
+            self.remove_note(&key);
+        }
+
+        // encrypted notes are only indexed by name, their content must not end up in the index
+        let content = match note.is_encrypted() {
+            true => String::new(),
+            false => note.get_content()?,
+        };
+
+        let front_matter = split_front_matter(&content)
+            .and_then(|(format, raw, _)| FrontMatter::parse(format, raw).ok());
+
+        let mut frequencies: BTreeMap<String, u32> = BTreeMap::new();
+        let mut length = 0;
+
+        for term in tokenize(&content) {
+            *frequencies.entry(term).or_insert(0) += 1;
+            length += 1;
+        }
+
+        for (term, count) in frequencies {
+            self.terms
+                .entry(term)
+                .or_default()
+                .insert(key.clone(), count);
+        }
+
+        let tags: BTreeSet<String> = parse_tags(&content);
+
+        self.notes.insert(
+            key,
+            IndexedNote {
+                modified,
+                size,
+                title: parse_pretty_name(&content),
+                created: front_matter
+                    .as_ref()
+                    .and_then(|front_matter| front_matter.created()),
+                aliases: front_matter
+                    .map(|front_matter| front_matter.aliases())
+                    .unwrap_or_default(),
+                tags,
+                headings: parse_headings(&content),
+                length,
+            },
+        );
+
+        self.dirty = true;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use colored::Colorize;
+
+use crate::{
+    actions::{
+        folder::list_options::TimeSpec,
+        trash::model::{Error, Trash, TrashItem},
+    },
+    output,
+};
+
+impl Trash {
+    /// Deletes the items in the trash for good, after asking. With `older_than` only the items
+    /// removed before that point in time are deleted. Returns the deleted items.
+    pub fn empty(&self, older_than: Option<TimeSpec>) -> Result<Vec<TrashItem>, Error> {
+        let cutoff = older_than.map(|older_than| older_than.start());
+
+        let items: Vec<TrashItem> = self
+            .items()?
+            .into_iter()
+            .filter(|item| {
+                cutoff
+                    .is_none_or(|cutoff| item.deleted_at().is_some_and(|deleted| deleted < cutoff))
+            })
+            .collect();
+
+        if items.is_empty() {
+            output::message("Nothing to remove from the trash");
+            return Ok(items);
+        }
+
+        let mut input = String::new();
+
+        output::message_inline(&format!(
+            "Are you sure you want to permanently delete {} items from the trash? {}/{} ",
+            items.len(),
+            "y".red(),
+            "N".green()
+        ));
+
+        std::io::stdin().read_line(&mut input)?;
+
+        if input.trim() != "y" {
+            output::message("Aborting!");
+            return Ok(Vec::new());
+        }
+
+        for item in items.iter() {
+            item.remove_permanently()?;
+        }
+
+        output::message(&format!("Removed {} items from the trash", items.len()));
+
+        Ok(items)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod empty;
+pub mod model;
+pub mod restore;
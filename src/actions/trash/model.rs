@@ -0,0 +1,233 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::debug;
+
+use crate::actions::{
+    folder::{
+        create::FolderCreationError, index::IndexError, list_options::parse_timestamp,
+        model::FolderError, sync::sync_note::SyncError,
+    },
+    note::{model::NoteError, open::timestamp},
+};
+
+/// The hidden folder in the data directory that holds removed notes and folders. Like every
+/// hidden folder it's skipped by `ls`, `search` and the index, and it's outside of the synced
+/// folders.
+pub const TRASH_DIR: &str = ".trash";
+
+/// Every item is stored as `.trash/<id>/<name>` next to `.trash/<id>.json`.
+const INFO_EXTENSION: &str = "json";
+
+#[derive(Error, Debug)]
+pub enum TrashError {
+    #[error("failed to access the trash: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to read the trash: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("'{0}' is not in the trash")]
+    NotFound(String),
+
+    #[error("'{0}' already exists, move it away before restoring")]
+    AlreadyExists(String),
+
+    #[error("'{0}' is not in the data directory")]
+    OutsideDataDir(String),
+
+    #[error(transparent)]
+    Index(#[from] IndexError),
+
+    #[error(transparent)]
+    Folder(#[from] FolderError),
+
+    #[error(transparent)]
+    CreateFolder(#[from] FolderCreationError),
+
+    #[error(transparent)]
+    Note(#[from] NoteError),
+
+    #[error(transparent)]
+    Sync(#[from] SyncError),
+}
+
+pub(super) type Error = TrashError;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashKind {
+    Note,
+    Folder,
+}
+
+/// Where an item came from and when it was removed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrashInfo {
+    /// The original path, relative to the data directory
+    pub path: String,
+    pub deleted: String,
+    pub kind: TrashKind,
+    /// The id the note had in its folder, so that restoring it gives it back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note_id: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrashItem {
+    pub id: String,
+    pub info: TrashInfo,
+    pub(super) root: PathBuf,
+}
+
+impl TrashItem {
+    /// The removed note or folder inside the trash.
+    pub fn content_path(&self) -> PathBuf {
+        let name = Path::new(&self.info.path)
+            .file_name()
+            .map(|name| name.to_os_string())
+            .unwrap_or_default();
+
+        self.root.join(&self.id).join(name)
+    }
+
+    pub(super) fn info_path(&self) -> PathBuf {
+        self.root.join(format!("{}.{}", self.id, INFO_EXTENSION))
+    }
+
+    pub fn deleted_at(&self) -> Option<DateTime<Local>> {
+        parse_timestamp(&self.info.deleted)
+    }
+
+    /// Deletes the item for good.
+    pub(super) fn remove_permanently(&self) -> Result<(), Error> {
+        let directory = self.root.join(&self.id);
+
+        if directory.exists() {
+            fs::remove_dir_all(&directory)?;
+        }
+
+        fs::remove_file(self.info_path())?;
+        debug!("Permanently removed '{}' from the trash", self.id);
+
+        Ok(())
+    }
+}
+
+pub struct Trash {
+    pub(super) data_dir: PathBuf,
+    pub(super) root: PathBuf,
+}
+
+impl Trash {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            data_dir: data_dir.to_path_buf(),
+            root: data_dir.join(TRASH_DIR),
+        }
+    }
+
+    /// Returns `path` relative to the data directory.
+    pub(super) fn relative_path(&self, path: &Path) -> Result<String, Error> {
+        path.strip_prefix(&self.data_dir)
+            .ok()
+            .and_then(|relative| relative.to_str())
+            .map(|relative| relative.to_string())
+            .ok_or_else(|| Error::OutsideDataDir(path.to_str().unwrap_or_default().to_string()))
+    }
+
+    /// Moves the note or folder at `path` into the trash. `note_id` is the id of a note in its
+    /// folder's index.
+    pub fn put(
+        &self,
+        path: &Path,
+        kind: TrashKind,
+        note_id: Option<usize>,
+    ) -> Result<TrashItem, Error> {
+        let relative = self.relative_path(path)?;
+        fs::create_dir_all(&self.root)?;
+
+        let stem = Local::now().format("%Y%m%d%H%M%S").to_string();
+        let mut id = stem.clone();
+        let mut counter = 1;
+        while self.root.join(&id).exists() {
+            id = format!("{}-{}", stem, counter);
+            counter += 1;
+        }
+
+        let item = TrashItem {
+            id,
+            info: TrashInfo {
+                path: relative,
+                deleted: timestamp(),
+                kind,
+                note_id,
+            },
+            root: self.root.clone(),
+        };
+
+        fs::create_dir(self.root.join(&item.id))?;
+        fs::rename(path, item.content_path())?;
+        fs::write(item.info_path(), serde_json::to_string_pretty(&item.info)?)?;
+
+        debug!("Moved '{}' to the trash as '{}'", item.info.path, item.id);
+
+        Ok(item)
+    }
+
+    /// Returns everything in the trash, oldest first.
+    pub fn items(&self) -> Result<Vec<TrashItem>, Error> {
+        if !self.root.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut items = Vec::new();
+
+        for entry in fs::read_dir(&self.root)? {
+            let path = entry?.path();
+
+            if path.extension().is_none_or(|ext| ext != INFO_EXTENSION) {
+                continue;
+            }
+
+            let id = match path.file_stem().and_then(|stem| stem.to_str()) {
+                None => continue,
+                Some(value) => value.to_string(),
+            };
+
+            items.push(TrashItem {
+                id,
+                info: serde_json::from_str(&fs::read_to_string(&path)?)?,
+                root: self.root.clone(),
+            });
+        }
+
+        items.sort_by(|a, b| {
+            a.info
+                .deleted
+                .cmp(&b.info.deleted)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        Ok(items)
+    }
+
+    /// Finds an item by its id, or by its original path. If the same path was removed more than
+    /// once, the latest one wins.
+    pub fn find(&self, selector: &str) -> Result<TrashItem, Error> {
+        let items = self.items()?;
+        let path = selector.trim_end_matches('/');
+
+        items
+            .iter()
+            .find(|item| item.id == selector)
+            .or_else(|| items.iter().rev().find(|item| item.info.path == path))
+            .cloned()
+            .ok_or_else(|| Error::NotFound(selector.to_string()))
+    }
+}
@@ -0,0 +1,59 @@
+use std::{fs, path::Path};
+
+use colored::Colorize;
+
+use crate::{
+    actions::{
+        folder::{
+            model::Folder,
+            sync::mutation::{Mutation, MutationAction},
+        },
+        note::model::Note,
+        trash::model::{Error, Trash, TrashItem, TrashKind},
+    },
+    config::model::Config,
+    output,
+};
+
+impl Trash {
+    /// Moves an item back to where it was removed from, creating missing folders on the way.
+    /// The restored note or folder is committed.
+    pub fn restore(&self, config: &Config, selector: &str) -> Result<TrashItem, Error> {
+        let item = self.find(selector)?;
+        let target = self.data_dir.join(&item.info.path);
+
+        if target.exists() {
+            return Err(Error::AlreadyExists(item.info.path.clone()));
+        }
+
+        let parent = Path::new(&item.info.path)
+            .parent()
+            .and_then(|parent| parent.to_str())
+            .unwrap_or("");
+        let folder = Folder::from_pathbuf(&self.data_dir, parent)?;
+        folder.create_all()?;
+
+        fs::rename(item.content_path(), &target)?;
+        item.remove_permanently()?;
+
+        if item.info.kind == TrashKind::Note {
+            let name = target.file_name().unwrap().to_str().unwrap();
+            folder.index_restore(name, item.info.note_id)?;
+        }
+
+        output::message(&format!("Restored '{}'", item.info.path.blue()));
+
+        match item.info.kind {
+            TrashKind::Note => {
+                let note = Note::from_pathbuf(&self.data_dir, item.info.path.clone())?;
+                folder.sync_note_as(&note, MutationAction::Restore, config)?;
+            }
+            TrashKind::Folder => {
+                Mutation::new(MutationAction::RestoreFolder, &item.info.path, vec![target])
+                    .commit(config)?;
+            }
+        }
+
+        Ok(item)
+    }
+}
@@ -1,6 +1,20 @@
+use chrono::NaiveDate;
 use clap::Subcommand;
 
-use crate::args::sync::actions::SetupSyncArgs;
+use crate::{
+    actions::{
+        folder::{
+            list_options::{ListSort, TimeSpec},
+            search_notes::SearchSort,
+        },
+        note::{front_matter::FrontMatterFormat, journal::JournalOffset, template::parse_variable},
+    },
+    args::{
+        config::actions::ConfigArgs, index::actions::IndexArgs, meta::actions::MetaArgs,
+        sync::actions::SetupSyncArgs, tasks::actions::TasksArgs, todo::actions::TodoArgs,
+        trash::actions::TrashArgs,
+    },
+};
 
 #[derive(Subcommand, Debug)]
 pub enum ActionArgs {
@@ -11,12 +25,60 @@ pub enum ActionArgs {
 
         #[arg(group = "create", short = 'n', help = "The note to create")]
         note: Option<String>,
+
+        #[arg(
+            long = "title",
+            help = "Write this title into the front matter of the new note"
+        )]
+        title: Option<String>,
+
+        #[arg(
+            long = "tag",
+            short = 't',
+            help = "Write this tag into the front matter of the new note. Can be given multiple times"
+        )]
+        tag: Vec<String>,
+
+        #[arg(
+            long = "front-matter",
+            help = "Create the note with a front matter in this format ('yaml' or 'toml')"
+        )]
+        front_matter: Option<FrontMatterFormat>,
+
+        #[arg(
+            long = "encrypt",
+            help = "Encrypt the note with a passphrase. The file gets an additional .age extension"
+        )]
+        encrypt: bool,
+
+        #[arg(
+            long = "template",
+            help = "Start the note from this template in .templates. With -f, make it the default for new notes in the folder"
+        )]
+        template: Option<String>,
+
+        #[arg(
+            long = "var",
+            help = "Fill in a template variable instead of asking for it, e.g. --var project=nb-rs. Can be given multiple times",
+            value_parser = parse_variable
+        )]
+        var: Vec<(String, String)>,
     },
 
     #[command(about = "Open a note", visible_aliases=["e", "edit"])]
     Open {
-        #[arg(help = "The note to open")]
-        note: String,
+        #[arg(
+            help = "The note to open, by name or id (e.g. '3' or 'work/12')",
+            required_unless_present = "tag"
+        )]
+        note: Option<String>,
+
+        #[arg(
+            long = "tag",
+            short = 't',
+            help = "Only consider notes with this tag. Can be given multiple times"
+        )]
+        tag: Vec<String>,
     },
 
     #[command(about = "List items in a folder")]
@@ -27,6 +89,72 @@ pub enum ActionArgs {
             required = false
         )]
         folder: String,
+
+        #[arg(
+            long = "tag",
+            short = 't',
+            help = "Only show notes with this tag. Can be given multiple times"
+        )]
+        tag: Vec<String>,
+
+        #[arg(
+            long = "tree",
+            short = 'r',
+            visible_alias = "recursive",
+            help = "Show all folders below as a tree with note counts"
+        )]
+        tree: bool,
+
+        #[arg(
+            long = "depth",
+            help = "Only show the tree down to this many levels. Implies --tree"
+        )]
+        depth: Option<usize>,
+
+        #[arg(
+            long = "sort",
+            short = 's',
+            help = "Sort by name, title, modified, created or size. Times and sizes are sorted newest and largest first"
+        )]
+        sort: Option<ListSort>,
+
+        #[arg(long = "reverse", help = "Reverse the order")]
+        reverse: bool,
+
+        #[arg(
+            long = "ext",
+            help = "Only show notes with this file extension. Can be given multiple times"
+        )]
+        ext: Vec<String>,
+
+        #[arg(
+            long = "since",
+            help = "Only show notes modified since then, e.g. 12h, 7d, 2w or 2026-01-31"
+        )]
+        since: Option<TimeSpec>,
+
+        #[arg(
+            long = "until",
+            help = "Only show notes modified until then, e.g. 7d or 2026-01-31"
+        )]
+        until: Option<TimeSpec>,
+
+        #[arg(
+            long = "limit",
+            short = 'l',
+            help = "Only show the first N notes (per folder with --tree)"
+        )]
+        limit: Option<usize>,
+
+        #[arg(
+            long = "notes-only",
+            help = "Only show notes",
+            conflicts_with_all = ["folders_only", "tree", "depth"]
+        )]
+        notes_only: bool,
+
+        #[arg(long = "folders-only", help = "Only show folders")]
+        folders_only: bool,
     },
 
     #[command(about = "Remove a folder or note")]
@@ -36,6 +164,35 @@ pub enum ActionArgs {
 
         #[arg(group = "remove", short = 'n', help = "The note to remove")]
         note: Option<String>,
+
+        #[arg(
+            group = "remove",
+            help = "The note to remove, by name or id (e.g. 'work/12')"
+        )]
+        selector: Option<String>,
+        #[arg(
+            long = "recursive",
+            short = 'r',
+            help = "Remove the folder with all notes and folders in it",
+            conflicts_with_all = ["note", "selector"]
+        )]
+        recursive: bool,
+
+        #[arg(
+            long = "force",
+            help = "Remove the folder even if it is a git repository",
+            conflicts_with_all = ["note", "selector"]
+        )]
+        force: bool,
+    },
+
+    #[command(about = "Move or rename a note or folder", visible_aliases=["move", "rename"])]
+    Mv {
+        #[arg(help = "The note (by name or id) or folder to move")]
+        source: String,
+
+        #[arg(help = "The new path. If this is an existing folder, the source is moved into it")]
+        destination: String,
     },
 
     #[command(about = "Query a string in notes", visible_aliases=["q", "s", "query"])]
@@ -47,8 +204,71 @@ pub enum ActionArgs {
         )]
         folder: String,
 
-        #[arg(help = "The content to search for")]
+        #[arg(
+            help = "The query to search for, e.g. 'kubernetes AND outage NOT draft' or 'title:retro \"action items\"'. \
+                    Supports AND, OR, NOT, parentheses, quoted phrases and the fields \
+                    title:, path:, tag:, ext: and modified:>2026-01-01"
+        )]
         content: String,
+
+        #[arg(
+            long = "regex",
+            short = 'E',
+            help = "Treat the whole content as one regular expression instead of a query",
+            conflicts_with = "fixed_strings"
+        )]
+        regex: bool,
+
+        #[arg(
+            long = "fixed-strings",
+            short = 'F',
+            help = "Treat the whole content as one literal string instead of a query"
+        )]
+        fixed_strings: bool,
+
+        #[arg(
+            long = "case-sensitive",
+            short = 's',
+            help = "Match upper and lower case exactly"
+        )]
+        case_sensitive: bool,
+
+        #[arg(long = "word", short = 'w', help = "Only match whole words")]
+        word: bool,
+
+        #[arg(
+            long = "tag",
+            short = 't',
+            help = "Only search notes with this tag. Can be given multiple times"
+        )]
+        tag: Vec<String>,
+
+        #[arg(
+            long = "sort",
+            help = "The order of the results: relevance, modified or path",
+            default_value = "relevance"
+        )]
+        sort: SearchSort,
+
+        #[arg(long = "limit", short = 'l', help = "Only show the first N results")]
+        limit: Option<usize>,
+
+        #[arg(
+            long = "decrypt",
+            short = 'd',
+            help = "Ask for a passphrase once and search encrypted notes too"
+        )]
+        decrypt: bool,
+    },
+
+    #[command(about = "List all tags and how many notes use them")]
+    Tags {
+        #[arg(
+            help = "Choose a specific folder",
+            default_value = ".",
+            required = false
+        )]
+        folder: String,
     },
 
     #[command(about = "Settings for git sync")]
@@ -63,4 +283,194 @@ pub enum ActionArgs {
         #[command(subcommand)]
         setup: Option<SetupSyncArgs>,
     },
+
+    #[command(about = "Manage the search index")]
+    Index {
+        #[command(subcommand)]
+        action: IndexArgs,
+    },
+
+    #[command(about = "Show or change the front matter of a note")]
+    Meta {
+        #[command(subcommand)]
+        action: MetaArgs,
+    },
+
+    #[command(about = "Add todos")]
+    Todo {
+        #[command(subcommand)]
+        action: TodoArgs,
+    },
+
+    #[command(about = "Mark a todo as done")]
+    Do {
+        #[arg(help = "The todo, by name or id")]
+        note: String,
+    },
+
+    #[command(about = "Mark a todo as not done")]
+    Undo {
+        #[arg(help = "The todo, by name or id")]
+        note: String,
+    },
+
+    #[command(about = "List the todos in a folder and all folders below it")]
+    Todos {
+        #[arg(help = "The folder to list the todos of", default_value = ".")]
+        folder: String,
+
+        #[arg(
+            long = "open",
+            help = "Only show todos that aren't done",
+            conflicts_with = "closed"
+        )]
+        open: bool,
+
+        #[arg(long = "closed", help = "Only show todos that are done")]
+        closed: bool,
+    },
+
+    #[command(about = "List the checkbox items in the notes of a folder and all folders below it")]
+    Tasks {
+        #[arg(help = "The folder to list the tasks of", default_value = ".")]
+        folder: String,
+
+        #[arg(
+            long = "open",
+            help = "Only show tasks that aren't checked",
+            conflicts_with = "closed"
+        )]
+        open: bool,
+
+        #[arg(long = "closed", help = "Only show tasks that are checked")]
+        closed: bool,
+
+        #[arg(
+            long = "owner",
+            short = 'o',
+            help = "Only show tasks that mention @owner"
+        )]
+        owner: Option<String>,
+
+        #[command(subcommand)]
+        action: Option<TasksArgs>,
+    },
+
+    #[command(about = "Open the journal note of today, creating it if needed")]
+    Daily {
+        #[arg(
+            help = "Move away from the date, e.g. yesterday, next or -2 days",
+            allow_negative_numbers = true
+        )]
+        offset: Option<JournalOffset>,
+
+        #[arg(
+            long = "date",
+            help = "Use this date instead of today, e.g. 2026-01-31"
+        )]
+        date: Option<NaiveDate>,
+    },
+
+    #[command(about = "Open the journal note of this week, creating it if needed")]
+    Weekly {
+        #[arg(
+            help = "Move away from the date, e.g. yesterday, next or -2 weeks",
+            allow_negative_numbers = true
+        )]
+        offset: Option<JournalOffset>,
+
+        #[arg(
+            long = "date",
+            help = "Use this date instead of today, e.g. 2026-01-31"
+        )]
+        date: Option<NaiveDate>,
+    },
+
+    #[command(about = "Open the journal note of this month, creating it if needed")]
+    Monthly {
+        #[arg(
+            help = "Move away from the date, e.g. yesterday, next or -2 months",
+            allow_negative_numbers = true
+        )]
+        offset: Option<JournalOffset>,
+
+        #[arg(
+            long = "date",
+            help = "Use this date instead of today, e.g. 2026-01-31"
+        )]
+        date: Option<NaiveDate>,
+    },
+
+    #[command(about = "List the revisions of a note")]
+    History {
+        #[arg(help = "The note, by name or id")]
+        note: String,
+
+        #[arg(long = "limit", short = 'l', help = "Show at most this many revisions")]
+        limit: Option<usize>,
+    },
+
+    #[command(about = "Print a note, or an old version of it")]
+    Show {
+        #[arg(help = "The note, by name or id")]
+        note: String,
+
+        #[arg(
+            long = "rev",
+            short = 'r',
+            help = "The revision to print, e.g. a hash from history or HEAD~2"
+        )]
+        rev: Option<String>,
+    },
+
+    #[command(about = "Show what changed in a note")]
+    Diff {
+        #[arg(help = "The note, by name or id")]
+        note: String,
+
+        #[arg(
+            help = "Compare these revisions. With one, it's compared to the current note. Without, the latest change is shown",
+            num_args = 0..=2
+        )]
+        revisions: Vec<String>,
+    },
+
+    #[command(about = "Roll a note back to an old version and commit that")]
+    Restore {
+        #[arg(help = "The note, by name or id")]
+        note: String,
+
+        #[arg(long = "rev", short = 'r', help = "The revision to restore")]
+        rev: String,
+    },
+
+    #[command(about = "List, restore or delete removed notes and folders")]
+    Trash {
+        #[command(subcommand)]
+        action: TrashArgs,
+    },
+
+    #[command(about = "Show or change settings in the config file")]
+    Config {
+        #[command(subcommand)]
+        action: ConfigArgs,
+    },
+
+    #[command(
+        hide = true,
+        about = "Merge three versions of a note block by block, run by git as a merge driver"
+    )]
+    MergeDriver {
+        #[arg(help = "The common ancestor (%O)")]
+        ancestor: String,
+
+        #[arg(help = "The local version, the result is written here (%A)")]
+        current: String,
+
+        #[arg(help = "The other version (%B)")]
+        other: String,
+
+        #[arg(help = "The path of the note in the repository (%P)")]
+        path: Option<String>,
+    },
 }
@@ -0,0 +1,25 @@
+use clap::Subcommand;
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigArgs {
+    #[command(about = "Print the value of a setting in the config file")]
+    Get {
+        #[arg(help = "The setting to print, e.g. 'editor' or 'sync.offline'")]
+        key: String,
+    },
+
+    #[command(about = "Change a setting in the config file")]
+    Set {
+        #[arg(help = "The setting to change, e.g. 'editor' or 'sync.offline'")]
+        key: String,
+
+        #[arg(help = "The new value")]
+        value: String,
+    },
+
+    #[command(about = "List all settings in the config file")]
+    List,
+
+    #[command(about = "Print the path to the config file")]
+    Path,
+}
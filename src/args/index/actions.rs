@@ -0,0 +1,10 @@
+use clap::Subcommand;
+
+#[derive(Subcommand, Debug)]
+pub enum IndexArgs {
+    #[command(about = "Throw away the search index and index every note again")]
+    Rebuild,
+
+    #[command(about = "Show how many notes are indexed and how many changed since")]
+    Status,
+}
@@ -0,0 +1,40 @@
+use clap::Subcommand;
+
+#[derive(Subcommand, Debug)]
+pub enum MetaArgs {
+    #[command(about = "Print a front matter value of a note")]
+    Get {
+        #[arg(help = "The note, by name or id")]
+        note: String,
+
+        #[arg(help = "The key to print, e.g. 'title'")]
+        key: String,
+    },
+
+    #[command(about = "Set a front matter value of a note")]
+    Set {
+        #[arg(help = "The note, by name or id")]
+        note: String,
+
+        #[arg(help = "The key to set, e.g. 'title'")]
+        key: String,
+
+        #[arg(help = "The new value. Lists can be written as '[a, b]'")]
+        value: String,
+    },
+
+    #[command(about = "Remove a front matter value from a note")]
+    Unset {
+        #[arg(help = "The note, by name or id")]
+        note: String,
+
+        #[arg(help = "The key to remove")]
+        key: String,
+    },
+
+    #[command(about = "List all front matter values of a note")]
+    List {
+        #[arg(help = "The note, by name or id")]
+        note: String,
+    },
+}
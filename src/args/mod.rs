@@ -1,3 +1,9 @@
 pub mod actions;
+pub mod config;
+pub mod index;
+pub mod meta;
 pub mod sync;
+pub mod tasks;
+pub mod todo;
 pub mod top;
+pub mod trash;
@@ -0,0 +1,16 @@
+use clap::Subcommand;
+
+#[derive(Subcommand, Debug)]
+pub enum TasksArgs {
+    #[command(about = "Check a task without opening the editor")]
+    Check {
+        #[arg(help = "The note and line of the task, e.g. work/3:12")]
+        task: String,
+    },
+
+    #[command(about = "Uncheck a task without opening the editor")]
+    Uncheck {
+        #[arg(help = "The note and line of the task, e.g. work/3:12")]
+        task: String,
+    },
+}
@@ -0,0 +1,22 @@
+use chrono::NaiveDate;
+use clap::Subcommand;
+
+#[derive(Subcommand, Debug)]
+pub enum TodoArgs {
+    #[command(about = "Add a todo note")]
+    Add {
+        #[arg(help = "What needs to be done")]
+        title: String,
+
+        #[arg(long = "due", help = "The date the todo is due, e.g. 2026-01-31")]
+        due: Option<NaiveDate>,
+
+        #[arg(
+            long = "folder",
+            short = 'f',
+            help = "The folder to add the todo to",
+            default_value = "."
+        )]
+        folder: String,
+    },
+}
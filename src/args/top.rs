@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use crate::args::actions::ActionArgs;
+use crate::{args::actions::ActionArgs, output::OutputFormat};
 
 use clap::Parser;
 
@@ -15,6 +15,14 @@ pub struct Args {
     #[arg(long = "offline", help = "I am offline. Do not sync with remote")]
     pub offline: bool,
 
+    #[arg(
+        long = "format",
+        global = true,
+        help = "The output format: text, json or ndjson",
+        default_value = "text"
+    )]
+    pub format: OutputFormat,
+
     #[command(subcommand)]
     pub action: ActionArgs,
 
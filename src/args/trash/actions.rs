@@ -0,0 +1,24 @@
+use clap::Subcommand;
+
+use crate::actions::folder::list_options::TimeSpec;
+
+#[derive(Subcommand, Debug)]
+pub enum TrashArgs {
+    #[command(about = "List the removed notes and folders")]
+    Ls,
+
+    #[command(about = "Move a removed note or folder back to where it was")]
+    Restore {
+        #[arg(help = "The id from trash ls, or the original path")]
+        item: String,
+    },
+
+    #[command(about = "Delete the items in the trash for good")]
+    Empty {
+        #[arg(
+            long = "older-than",
+            help = "Only delete items removed before this, e.g. 30d, 2w or 2026-01-31"
+        )]
+        older_than: Option<TimeSpec>,
+    },
+}
@@ -0,0 +1,245 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use resolve_path::PathResolveExt;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::debug;
+
+use crate::config::model::ColorMode;
+
+#[derive(Error, Debug)]
+pub enum ConfigFileError {
+    #[error("failed to read config file '{}': {}", .path, .err)]
+    Read { path: String, err: std::io::Error },
+
+    #[error("failed to parse config file '{}': {}", .path, .err)]
+    Parse { path: String, err: toml::de::Error },
+
+    #[error("failed to serialize config: {0}")]
+    Serialize(#[from] toml::ser::Error),
+
+    #[error("unknown config key '{0}'")]
+    UnknownKey(String),
+
+    #[error("invalid value '{}' for '{}': expected {}", .value, .key, .expected)]
+    InvalidValue {
+        key: String,
+        value: String,
+        expected: &'static str,
+    },
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+type Error = ConfigFileError;
+
+/// The contents of `config.toml`. Every field is optional so that unset values can fall through to
+/// the defaults in [`crate::config::model::Config`].
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub data_dir: Option<String>,
+    pub editor: Option<String>,
+    pub default_extension: Option<String>,
+    pub commit_message: Option<String>,
+    pub color: Option<ColorMode>,
+
+    #[serde(default)]
+    pub sync: SyncSection,
+
+    #[serde(default)]
+    pub journal: JournalSection,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SyncSection {
+    pub offline: Option<bool>,
+    pub auto_push: Option<bool>,
+    pub pull_before_open: Option<bool>,
+}
+
+/// Path patterns of the journal notes, formatted with strftime, e.g. `journal/%Y/%Y-%m-%d.md`.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct JournalSection {
+    pub daily: Option<String>,
+    pub weekly: Option<String>,
+    pub monthly: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+enum KeyKind {
+    String,
+    Bool,
+    Color,
+}
+
+impl KeyKind {
+    fn expected(&self) -> &'static str {
+        match self {
+            KeyKind::String => "a string",
+            KeyKind::Bool => "'true' or 'false'",
+            KeyKind::Color => "'auto', 'always' or 'never'",
+        }
+    }
+}
+
+/// All keys understood by `config get/set`, in the order `config list` prints them.
+const KEYS: &[(&str, KeyKind)] = &[
+    ("data_dir", KeyKind::String),
+    ("editor", KeyKind::String),
+    ("default_extension", KeyKind::String),
+    ("commit_message", KeyKind::String),
+    ("color", KeyKind::Color),
+    ("sync.offline", KeyKind::Bool),
+    ("sync.auto_push", KeyKind::Bool),
+    ("sync.pull_before_open", KeyKind::Bool),
+    ("journal.daily", KeyKind::String),
+    ("journal.weekly", KeyKind::String),
+    ("journal.monthly", KeyKind::String),
+];
+
+impl ConfigFile {
+    /// Returns the location of the config file. `NB_RS_CONFIG` wins, then `$XDG_CONFIG_HOME`,
+    /// then `~/.config`.
+    pub fn path() -> PathBuf {
+        if let Ok(path) = env::var("NB_RS_CONFIG") {
+            return PathBuf::from(path.resolve());
+        }
+
+        let mut path = match env::var("XDG_CONFIG_HOME") {
+            Ok(value) if !value.is_empty() => PathBuf::from(value),
+            _ => PathBuf::from("~/.config".resolve()),
+        };
+
+        path.push("nb-rs");
+        path.push("config.toml");
+
+        path
+    }
+
+    /// Loads the config file, returning an empty config if it does not exist.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let table = Self::load_table(path)?;
+
+        table
+            .try_into()
+            .map_err(|err: toml::de::Error| Error::Parse {
+                path: path.to_str().unwrap().to_string(),
+                err,
+            })
+    }
+
+    fn load_table(path: &Path) -> Result<toml::Table, Error> {
+        if !path.exists() {
+            debug!("No config file at '{}'", path.to_str().unwrap());
+            return Ok(toml::Table::new());
+        }
+
+        let content = fs::read_to_string(path).map_err(|err| Error::Read {
+            path: path.to_str().unwrap().to_string(),
+            err,
+        })?;
+
+        content.parse::<toml::Table>().map_err(|err| Error::Parse {
+            path: path.to_str().unwrap().to_string(),
+            err,
+        })
+    }
+
+    fn key_kind(key: &str) -> Result<KeyKind, Error> {
+        KEYS.iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, kind)| *kind)
+            .ok_or_else(|| Error::UnknownKey(key.to_string()))
+    }
+
+    /// Returns the value of `key` as stored in the file, or `None` if it is not set.
+    pub fn get(path: &Path, key: &str) -> Result<Option<String>, Error> {
+        Self::key_kind(key)?;
+        let table = Self::load_table(path)?;
+
+        let mut current = &table;
+        let mut parts = key.split('.').peekable();
+
+        while let Some(part) = parts.next() {
+            let value = match current.get(part) {
+                None => return Ok(None),
+                Some(value) => value,
+            };
+
+            if parts.peek().is_none() {
+                return Ok(Some(match value {
+                    toml::Value::String(value) => value.clone(),
+                    other => other.to_string(),
+                }));
+            }
+
+            current = match value.as_table() {
+                None => return Ok(None),
+                Some(table) => table,
+            };
+        }
+
+        Ok(None)
+    }
+
+    /// Sets `key` to `value` in the file, creating the file and its parent folders if needed.
+    pub fn set(path: &Path, key: &str, value: &str) -> Result<(), Error> {
+        let kind = Self::key_kind(key)?;
+
+        let invalid = || Error::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            expected: kind.expected(),
+        };
+
+        let parsed = match kind {
+            KeyKind::String => toml::Value::String(value.to_string()),
+            KeyKind::Bool => toml::Value::Boolean(value.parse().map_err(|_| invalid())?),
+            KeyKind::Color => {
+                value.parse::<ColorMode>().map_err(|_| invalid())?;
+                toml::Value::String(value.to_string())
+            }
+        };
+
+        let mut table = Self::load_table(path)?;
+
+        let mut current = &mut table;
+        let mut parts: Vec<&str> = key.split('.').collect();
+        let last = parts.pop().unwrap();
+
+        for part in parts {
+            current = current
+                .entry(part)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .ok_or_else(invalid)?;
+        }
+
+        current.insert(last.to_string(), parsed);
+
+        // make sure we never write a file that we can't read back
+        let _: ConfigFile = table.clone().try_into().map_err(|_| invalid())?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string_pretty(&table)?)?;
+
+        Ok(())
+    }
+
+    /// Returns every known key together with its value in the file, if set.
+    pub fn list(path: &Path) -> Result<Vec<(&'static str, Option<String>)>, Error> {
+        KEYS.iter()
+            .map(|(key, _)| Ok((*key, Self::get(path, key)?)))
+            .collect()
+    }
+}
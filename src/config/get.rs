@@ -1,34 +1,118 @@
-use std::{env, path::PathBuf};
+use std::{env, path::PathBuf, str::FromStr};
 
 use resolve_path::PathResolveExt;
 use thiserror::Error;
 
-use crate::{args::top::Args, config::model::Config};
+use crate::{
+    args::top::Args,
+    config::{
+        file::{ConfigFile, ConfigFileError},
+        model::{ColorMode, Config},
+    },
+};
 
 #[derive(Error, Debug)]
 pub enum ConfigGetError {
     // #[error("no data_dir was passed in. Either use '-D' or use the config file!")]
     // NoDataDir,
-    #[error("no EDITOR was found: {0}")]
+    #[error("no editor was found. Set 'editor' in the config file, NB_RS_EDITOR or EDITOR: {0}")]
     NoEditor(String),
+
+    #[error("invalid value '{}' in environment variable {}", .value, .var)]
+    InvalidEnv { var: String, value: String },
+
+    #[error(transparent)]
+    ConfigFile(#[from] ConfigFileError),
+}
+
+/// Reads the `NB_RS_*` override for a config key, e.g. `sync.offline` -> `NB_RS_SYNC_OFFLINE`.
+fn env_override(key: &str) -> Option<String> {
+    let var = format!("NB_RS_{}", key.replace('.', "_").to_uppercase());
+    env::var(var).ok()
+}
+
+fn env_override_parsed<T: FromStr>(key: &str) -> Result<Option<T>, ConfigGetError> {
+    match env_override(key) {
+        None => Ok(None),
+        Some(value) => match value.parse() {
+            Ok(parsed) => Ok(Some(parsed)),
+            Err(_) => Err(ConfigGetError::InvalidEnv {
+                var: format!("NB_RS_{}", key.replace('.', "_").to_uppercase()),
+                value,
+            }),
+        },
+    }
 }
 
 impl Config {
+    /// Resolves the config. Precedence is CLI flags > `NB_RS_*` env vars > config file > defaults.
     pub fn new(args: &Args) -> Result<Self, ConfigGetError> {
+        let file = ConfigFile::load(&ConfigFile::path())?;
+
         let data_dir: PathBuf = match args.data_dir.clone() {
             None => {
-                let path = "~/.nb-rs/".resolve();
-                PathBuf::from(path)
+                let path = env_override("data_dir")
+                    .or(file.data_dir)
+                    .unwrap_or("~/.nb-rs/".to_string());
+                PathBuf::from(path.resolve())
             }
             Some(value) => value,
         };
 
-        let editor = env::var("EDITOR").map_err(|e| ConfigGetError::NoEditor(e.to_string()))?;
+        let editor = match env_override("editor").or(file.editor) {
+            Some(value) => value,
+            None => env::var("EDITOR").map_err(|e| ConfigGetError::NoEditor(e.to_string()))?,
+        };
+
+        let default_extension = env_override("default_extension")
+            .or(file.default_extension)
+            .unwrap_or("md".to_string());
+
+        let commit_message = env_override("commit_message")
+            .or(file.commit_message)
+            .unwrap_or("[nb-rs] {action}: {name}".to_string());
+
+        let color = env_override_parsed::<ColorMode>("color")?
+            .or(file.color)
+            .unwrap_or_default();
+
+        let offline = args.offline
+            || env_override_parsed("sync.offline")?
+                .or(file.sync.offline)
+                .unwrap_or(false);
+
+        let auto_push = env_override_parsed("sync.auto_push")?
+            .or(file.sync.auto_push)
+            .unwrap_or(true);
+
+        let pull_before_open = env_override_parsed("sync.pull_before_open")?
+            .or(file.sync.pull_before_open)
+            .unwrap_or(true);
+
+        let journal_daily = env_override("journal.daily")
+            .or(file.journal.daily)
+            .unwrap_or("journal/%Y/%Y-%m-%d.md".to_string());
+
+        let journal_weekly = env_override("journal.weekly")
+            .or(file.journal.weekly)
+            .unwrap_or("journal/%G/%G-W%V.md".to_string());
+
+        let journal_monthly = env_override("journal.monthly")
+            .or(file.journal.monthly)
+            .unwrap_or("journal/%Y/%Y-%m.md".to_string());
 
         Ok(Config {
             data_dir,
             editor,
-            offline: args.offline,
+            default_extension,
+            commit_message,
+            color,
+            offline,
+            auto_push,
+            pull_before_open,
+            journal_daily,
+            journal_weekly,
+            journal_monthly,
         })
     }
 }
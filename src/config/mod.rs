@@ -1,2 +1,3 @@
+pub mod file;
 pub mod get;
 pub mod model;
@@ -1,9 +1,91 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            other => Err(other.to_string()),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct Config {
     pub data_dir: PathBuf,
     pub editor: String,
+    pub default_extension: String,
+    pub commit_message: String,
+    pub color: ColorMode,
 
     pub offline: bool,
+    pub auto_push: bool,
+    pub pull_before_open: bool,
+
+    pub journal_daily: String,
+    pub journal_weekly: String,
+    pub journal_monthly: String,
+}
+
+impl Config {
+    /// Builds a commit message from the `commit_message` format.
+    /// `{action}` and `{name}` are replaced with the given values.
+    pub fn commit_message(&self, action: &str, name: &str) -> String {
+        self.commit_message
+            .replace("{action}", action)
+            .replace("{name}", name)
+    }
+
+    /// Appends the default extension to `name` if it doesn't have one yet.
+    pub fn note_file_name(&self, name: &str) -> String {
+        let has_extension = name
+            .rsplit('/')
+            .next()
+            .map(|file| file.contains('.'))
+            .unwrap_or(false);
+
+        if has_extension || self.default_extension.is_empty() {
+            return name.to_string();
+        }
+
+        format!(
+            "{}.{}",
+            name,
+            self.default_extension.trim_start_matches('.')
+        )
+    }
+}
+
+#[cfg(test)]
+impl Config {
+    /// The defaults for a notebook in `data_dir`, offline so that nothing is pushed.
+    pub fn for_tests(data_dir: &std::path::Path) -> Self {
+        Self {
+            data_dir: data_dir.to_path_buf(),
+            editor: "true".to_string(),
+            default_extension: "md".to_string(),
+            commit_message: "[nb-rs] {action}: {name}".to_string(),
+            color: ColorMode::Never,
+            offline: true,
+            auto_push: false,
+            pull_before_open: false,
+            journal_daily: String::new(),
+            journal_weekly: String::new(),
+            journal_monthly: String::new(),
+        }
+    }
 }
@@ -3,26 +3,54 @@ use fuzzy_select::FuzzySelect;
 use std::{
     collections::HashMap,
     fs::{self},
+    path::Path,
 };
 
 use anyhow::Result;
+use chrono::{Local, NaiveDate};
 use clap::{CommandFactory, FromArgMatches, error::ErrorKind};
 use itertools::Itertools;
+use serde_json::json;
 use tracing::{debug, error};
 use tracing_subscriber::EnvFilter;
 
 use crate::{
     actions::{
-        folder::model::Folder,
-        note::model::{Note, NoteError},
+        folder::{
+            list_options::ListOptions,
+            model::Folder,
+            search_matcher::SearchOptions,
+            search_notes::sort_search_results,
+            search_query::SearchQuery,
+            sync::{merge::merge_notes, mutation::MutationAction},
+        },
+        note::{
+            encryption::{ENCRYPTED_EXTENSION, prompt_new_passphrase, prompt_passphrase},
+            front_matter::FrontMatter,
+            journal::{JournalOffset, Period},
+            meta::MetaError,
+            model::{Note, NoteError},
+            open::timestamp,
+            tasks::parse_task_selector,
+            template::{Template, TemplateContext, split_rendered},
+        },
+        search_index::model::SearchIndex,
+        trash::model::{Trash, TrashKind},
+    },
+    args::{
+        config::actions::ConfigArgs, index::actions::IndexArgs, meta::actions::MetaArgs,
+        tasks::actions::TasksArgs, todo::actions::TodoArgs, top::Args, trash::actions::TrashArgs,
+    },
+    config::{
+        file::ConfigFile,
+        model::{ColorMode, Config},
     },
-    args::top::Args,
-    config::model::Config,
 };
 
 mod actions;
 mod args;
 mod config;
+mod output;
 
 pub static GIT_REV: &str = env!("GIT_REV");
 
@@ -49,12 +77,54 @@ fn main() -> Result<()> {
         _ => "trace",
     };
 
+    output::set_format(args.format);
+
+    // logs go to stderr with structured output, so that they don't break the JSON on stdout
+    match output::is_text() {
+        true => tracing_subscriber::fmt()
+            .with_line_number(true)
+            .with_env_filter(EnvFilter::new(level))
+            .init(),
+        false => tracing_subscriber::fmt()
+            .with_line_number(true)
+            .with_env_filter(EnvFilter::new(level))
+            .with_writer(std::io::stderr)
+            .init(),
+    }
+
+    match run(args) {
+        Err(e) if !output::is_text() => {
+            output::emit_error(&e)?;
+            std::process::exit(1);
+        }
+        result => result,
+    }
+}
+
+fn run(args: Args) -> Result<()> {
+    // handled before loading the config, so that a broken config file can still be fixed
+    if let args::actions::ActionArgs::Config { action } = &args.action {
+        return run_config_action(action);
+    }
+
+    // git runs it in the middle of a merge, it doesn't need the notebook
+    if let args::actions::ActionArgs::MergeDriver {
+        ancestor,
+        current,
+        other,
+        path,
+    } = &args.action
+    {
+        return run_merge_driver(ancestor, current, other, path.as_deref());
+    }
+
     let config = Config::new(&args)?;
 
-    tracing_subscriber::fmt()
-        .with_line_number(true)
-        .with_env_filter(EnvFilter::new(level))
-        .init();
+    match config.color {
+        ColorMode::Always => colored::control::set_override(true),
+        ColorMode::Never => colored::control::set_override(false),
+        ColorMode::Auto => {}
+    }
 
     if !config.data_dir.exists() {
         match fs::create_dir(config.data_dir.clone()) {
@@ -81,25 +151,107 @@ fn main() -> Result<()> {
     }
 
     match args.action {
-        args::actions::ActionArgs::Create { folder, note } => {
+        args::actions::ActionArgs::Create {
+            folder,
+            note,
+            title,
+            tag,
+            front_matter,
+            encrypt,
+            template,
+            var,
+        } => {
             if let Some(folder) = folder {
                 let folder = Folder::from_pathbuf(&config.data_dir, &folder)?;
-                folder.create()?;
+
+                // setting the default template of an existing folder is fine
+                if template.is_none() || !folder.get_path().is_dir() {
+                    folder.create(&config)?;
+                }
+
+                if let Some(template) = &template {
+                    folder.set_default_template(&config, template)?;
+                }
+
+                output::emit_success(
+                    "create",
+                    json!({
+                        "folder": output::relative_path(&folder.get_path(), &config.data_dir),
+                        "template": template,
+                    }),
+                )?;
             }
 
             if let Some(note) = note {
-                let note = Note::new_create(config.data_dir.to_str().unwrap(), note)?;
-                note.open(&config)?;
+                let name = config.note_file_name(&note);
+                let (template_front_matter, body) =
+                    render_note_template(&config, &name, title.as_deref(), template, var)?;
+
+                let has_flags = title.is_some() || !tag.is_empty() || front_matter.is_some();
+                let front_matter = match (template_front_matter, has_flags) {
+                    (base, false) => base,
+                    (base, true) => {
+                        let mut front_matter = base
+                            .unwrap_or_else(|| FrontMatter::new(front_matter.unwrap_or_default()));
+                        if let Some(title) = title {
+                            front_matter.set("title", &title);
+                        }
+                        if !tag.is_empty() {
+                            front_matter.set_list("tags", &tag);
+                        }
+                        front_matter.set("created", &timestamp());
+                        Some(front_matter)
+                    }
+                };
+
+                let passphrase = match encrypt {
+                    true => Some(prompt_new_passphrase()?),
+                    false => None,
+                };
+
+                let name = match encrypt {
+                    true => format!("{}.{}", name, ENCRYPTED_EXTENSION),
+                    false => name,
+                };
+
+                let note = Note::new_create(
+                    config.data_dir.to_str().unwrap(),
+                    name,
+                    front_matter.as_ref(),
+                    &body,
+                    passphrase.as_ref(),
+                )?;
+                Folder::from_note(&note).sync_note_as(&note, MutationAction::Create, &config)?;
+
+                match &passphrase {
+                    Some(passphrase) => note.open_encrypted(&config, passphrase)?,
+                    None => note.open(&config)?,
+                }
+                output::emit_success(
+                    "create",
+                    json!({ "note": output::relative_path(&note.get_path(), &config.data_dir) }),
+                )?;
             }
         }
-        args::actions::ActionArgs::Open { note: note_string } => {
-            let note = Note::from_pathbuf(&config.data_dir, note_string.clone());
+        args::actions::ActionArgs::Open {
+            note: note_string,
+            tag,
+        } => {
+            let note_string = note_string.unwrap_or_default();
+            let note = match Note::from_selector(&config.data_dir, &note_string) {
+                Ok(note) if !note.has_tags(&tag)? => {
+                    Err(NoteError::NoteDoesNotExist(note_string.clone()))
+                }
+                other => other,
+            };
             let note = match note {
                 Ok(note) => note,
                 Err(e) => match e {
                     NoteError::NoteDoesNotExist(non_existant_note) => {
+                        let index = SearchIndex::open(&config.data_dir)?;
                         let folder = Folder::from_pathbuf(&config.data_dir, ".")?;
-                        let notes = folder.get_notes_by_name(&note_string.to_lowercase())?;
+                        let notes =
+                            folder.get_notes_by_name(&note_string.to_lowercase(), &tag, &index)?;
                         match notes.len() {
                             0 => {
                                 return Err(NoteError::NoteDoesNotExist(non_existant_note).into());
@@ -111,7 +263,7 @@ fn main() -> Result<()> {
                                 let mut options = Vec::new();
 
                                 for note in notes {
-                                    let name = note.get_name()?;
+                                    let name = index.name_of(&note)?;
                                     options.push(name.clone());
                                     map.insert(name, note);
                                 }
@@ -136,31 +288,148 @@ fn main() -> Result<()> {
             };
 
             note.open(&config)?;
+            output::emit_success(
+                "open",
+                json!({ "note": output::relative_path(&note.get_path(), &config.data_dir) }),
+            )?;
         }
-        args::actions::ActionArgs::Ls { folder } => {
+        args::actions::ActionArgs::Ls {
+            folder,
+            tag,
+            tree,
+            depth,
+            sort,
+            reverse,
+            ext,
+            since,
+            until,
+            limit,
+            notes_only,
+            folders_only,
+        } => {
+            let index = SearchIndex::open(&config.data_dir)?;
             let folder = Folder::from_pathbuf(&config.data_dir, folder)?;
-            let result = folder.list()?;
-            result.print();
+            let options = ListOptions {
+                sort,
+                reverse,
+                ext,
+                since,
+                until,
+                limit,
+                notes_only,
+                folders_only,
+            };
+
+            if tree || depth.is_some() {
+                let tree = folder.list_tree(&tag, &options, &index)?;
+                match output::is_text() {
+                    true => tree.print(&index, depth),
+                    false => output::emit_list(&tree.entries(&config.data_dir, &index, depth)?)?,
+                }
+                return Ok(());
+            }
+
+            let mut result = folder.list()?;
+            result.retain_tagged(&tag, &index)?;
+            result.apply(&options, &index)?;
+
+            match output::is_text() {
+                true => result.print(&index),
+                false => output::emit_list(&result.entries(&config.data_dir, &index)?)?,
+            }
         }
-        args::actions::ActionArgs::Rm { folder, note } => {
-            if let Some(note) = note {
-                let note = Note::from_pathbuf(&config.data_dir, note)?;
-                note.remove(&config)?;
+        args::actions::ActionArgs::Rm {
+            folder,
+            note,
+            selector,
+            recursive,
+            force,
+        } => {
+            if let Some(note) = note.or(selector) {
+                let note = Note::from_selector(&config.data_dir, &note)?;
+                let item = note.remove(&config)?;
+                output::emit_success(
+                    "remove",
+                    json!({
+                        "note": output::relative_path(&note.get_path(), &config.data_dir),
+                        "removed": item.is_some(),
+                        "trash": item.map(|item| item.id),
+                    }),
+                )?;
             }
 
             if let Some(folder) = folder {
                 let folder = Folder::from_pathbuf(&config.data_dir, &folder)?;
-                folder.remove()?;
+                let item = folder.remove(&config, recursive, force)?;
+                output::emit_success(
+                    "remove",
+                    json!({
+                        "folder": output::relative_path(&folder.get_path(), &config.data_dir),
+                        "removed": item.is_some(),
+                        "trash": item.map(|item| item.id),
+                    }),
+                )?;
+            }
+        }
+        args::actions::ActionArgs::Mv {
+            source,
+            destination,
+        } => {
+            if config.data_dir.join(&source).is_dir() {
+                let folder = Folder::from_pathbuf(&config.data_dir, &source)?;
+                folder.move_to(&config, &destination)?;
+            } else {
+                let note = Note::from_selector(&config.data_dir, &source)?;
+                note.move_to(&config, &destination)?;
             }
+            output::emit_success(
+                "move",
+                json!({ "source": source, "destination": destination }),
+            )?;
         }
         args::actions::ActionArgs::Search {
             content: term,
             folder,
+            tag,
+            regex,
+            fixed_strings,
+            case_sensitive,
+            word,
+            sort,
+            limit,
+            decrypt,
         } => {
-            let term = term.to_lowercase();
             debug!("searching for term={term}, folder={folder}");
+            let options = SearchOptions {
+                regex,
+                case_sensitive,
+                word,
+            };
+            let query = match regex || fixed_strings {
+                true => SearchQuery::literal(&term, options)?,
+                false => SearchQuery::parse(&term, options)?,
+            };
+            let index = SearchIndex::open(&config.data_dir)?;
             let folder = Folder::from_pathbuf(&config.data_dir, folder)?;
-            let found_notes = folder.search_notes_content(&term)?;
+            let passphrase = match decrypt {
+                true => Some(prompt_passphrase("Passphrase for encrypted notes: ")?),
+                false => None,
+            };
+            let mut found_notes =
+                folder.search_notes_content(&query, &tag, &index, passphrase.as_ref())?;
+            sort_search_results(&mut found_notes, sort);
+            if let Some(limit) = limit {
+                found_notes.truncate(limit);
+            }
+
+            if !output::is_text() {
+                let mut entries = Vec::new();
+                for result in found_notes.iter() {
+                    entries.append(&mut result.entries(&config.data_dir, &index)?);
+                }
+                output::emit_list(&entries)?;
+                return Ok(());
+            }
 
             match found_notes.is_empty() {
                 true => {
@@ -172,8 +441,9 @@ fn main() -> Result<()> {
                         found_notes
                             .into_iter()
                             .map(|result| format!(
-                                "- {}\n{}",
+                                "- {} {}\n{}",
                                 result.note.name.blue(),
+                                format!("({:.2})", result.score).yellow(),
                                 result
                                     .snippets
                                     .into_iter()
@@ -185,6 +455,34 @@ fn main() -> Result<()> {
                 }
             }
         }
+        args::actions::ActionArgs::Tags { folder } => {
+            let index = SearchIndex::open(&config.data_dir)?;
+            let folder = Folder::from_pathbuf(&config.data_dir, folder)?;
+            let tags = folder.list_tags(&index)?;
+
+            if !output::is_text() {
+                let entries: Vec<_> = tags
+                    .into_iter()
+                    .sorted_by(|(a_tag, a), (b_tag, b)| b.cmp(a).then(a_tag.cmp(b_tag)))
+                    .map(|(tag, count)| json!({ "tag": tag, "count": count }))
+                    .collect();
+                output::emit_list(&entries)?;
+                return Ok(());
+            }
+
+            match tags.is_empty() {
+                true => println!("{}", "No tags found!".yellow()),
+                false => {
+                    println!("Found {} tags!", tags.len());
+                    for (tag, count) in tags
+                        .into_iter()
+                        .sorted_by(|(a_tag, a), (b_tag, b)| b.cmp(a).then(a_tag.cmp(b_tag)))
+                    {
+                        println!("- {} ({})", format!("#{}", tag).blue(), count);
+                    }
+                }
+            }
+        }
         args::actions::ActionArgs::Sync { setup, folder } => {
             let folder = Folder::from_pathbuf(&config.data_dir, folder)?;
             match setup {
@@ -198,7 +496,552 @@ fn main() -> Result<()> {
                     }
                 },
             }
+            output::emit_success(
+                "sync",
+                json!({ "folder": output::relative_path(&folder.get_path(), &config.data_dir) }),
+            )?;
+        }
+        args::actions::ActionArgs::Index { action } => {
+            let mut index = SearchIndex::load(&config.data_dir);
+            match action {
+                IndexArgs::Rebuild => {
+                    output::message_inline("Rebuilding the search index... ");
+
+                    let changes = index.rebuild()?;
+                    index.save()?;
+
+                    output::message(&"Done".green().to_string());
+                    output::message(&format!("Indexed {} notes", changes.added));
+                    output::emit_success("index rebuild", json!({ "notes": changes.added }))?;
+                }
+                IndexArgs::Status => {
+                    let changes = index.status()?;
+
+                    if !output::is_text() {
+                        output::emit(&json!({
+                            "path": SearchIndex::path(&config.data_dir),
+                            "notes": index.notes.len(),
+                            "terms": index.terms.len(),
+                            "up_to_date": changes.is_empty(),
+                            "added": changes.added,
+                            "changed": changes.changed,
+                            "removed": changes.removed,
+                        }))?;
+                        return Ok(());
+                    }
+
+                    println!(
+                        "{} notes and {} terms indexed in '{}'",
+                        index.notes.len(),
+                        index.terms.len(),
+                        SearchIndex::path(&config.data_dir).to_str().unwrap().blue()
+                    );
+
+                    match changes.is_empty() {
+                        true => println!("{}", "The index is up to date".green()),
+                        false => println!(
+                            "{} {} new, {} changed, {} removed",
+                            "Out of date:".yellow(),
+                            changes.added,
+                            changes.changed,
+                            changes.removed
+                        ),
+                    }
+                }
+            }
+        }
+        args::actions::ActionArgs::Meta { action } => match action {
+            MetaArgs::Get { note, key } => {
+                let note = Note::from_selector(&config.data_dir, &note)?;
+                match note.get_front_matter()?.and_then(|fm| fm.get(&key)) {
+                    Some(value) if !output::is_text() => {
+                        output::emit(&json!({ "key": key, "value": value }))?
+                    }
+                    Some(value) => println!("{}", value),
+                    None => return Err(MetaError::KeyNotSet(key).into()),
+                }
+            }
+            MetaArgs::Set { note, key, value } => {
+                let note = Note::from_selector(&config.data_dir, &note)?;
+                note.meta_set(&config, &key, &value)?;
+                output::emit_success("meta set", json!({ "key": key, "value": value }))?;
+            }
+            MetaArgs::Unset { note, key } => {
+                let note = Note::from_selector(&config.data_dir, &note)?;
+                note.meta_unset(&config, &key)?;
+                output::emit_success("meta unset", json!({ "key": key }))?;
+            }
+            MetaArgs::List { note } => {
+                let note = Note::from_selector(&config.data_dir, &note)?;
+                let front_matter = note.get_front_matter()?;
+
+                if !output::is_text() {
+                    let mut values = serde_json::Map::new();
+                    if let Some(front_matter) = &front_matter {
+                        for key in front_matter.keys() {
+                            values.insert(key.clone(), json!(front_matter.get(&key)));
+                        }
+                    }
+                    output::emit(&values)?;
+                    return Ok(());
+                }
+
+                match front_matter {
+                    None => println!("{}", "The note has no front matter!".yellow()),
+                    Some(front_matter) => {
+                        for key in front_matter.keys() {
+                            println!(
+                                "{}: {}",
+                                key.blue(),
+                                front_matter.get(&key).unwrap_or_default()
+                            );
+                        }
+                    }
+                }
+            }
+        },
+        args::actions::ActionArgs::Todo { action } => match action {
+            TodoArgs::Add { title, due, folder } => {
+                let folder = Folder::from_pathbuf(&config.data_dir, folder)?;
+                let mut note = folder.todo_add(&config, &title, due)?;
+                note.id = folder.index_load()?.id_of(&note.name);
+
+                output::message(&format!(
+                    "Added todo {}",
+                    format!("[{}]", note.selector(&config.data_dir)).yellow()
+                ));
+                output::emit_success(
+                    "todo add",
+                    json!({
+                        "note": output::relative_path(&note.get_path(), &config.data_dir),
+                        "selector": note.selector(&config.data_dir),
+                    }),
+                )?;
+            }
+        },
+        args::actions::ActionArgs::Do { note } => run_set_todo_done(&config, &note, true)?,
+        args::actions::ActionArgs::Undo { note } => run_set_todo_done(&config, &note, false)?,
+        args::actions::ActionArgs::Todos {
+            folder,
+            open,
+            closed,
+        } => {
+            let folder = Folder::from_pathbuf(&config.data_dir, folder)?;
+            let mut todos = folder.list_todos()?;
+            todos.retain(|(_, todo)| (!open || !todo.done) && (!closed || todo.done));
+
+            if !output::is_text() {
+                let entries: Vec<_> = todos
+                    .iter()
+                    .map(|(note, todo)| {
+                        json!({
+                            "note": output::relative_path(&note.get_path(), &config.data_dir),
+                            "selector": note.selector(&config.data_dir),
+                            "title": todo.title,
+                            "done": todo.done,
+                            "due": todo.due.map(|due| due.to_string()),
+                        })
+                    })
+                    .collect();
+                output::emit_list(&entries)?;
+                return Ok(());
+            }
+
+            match todos.is_empty() {
+                true => println!("{}", "No todos found!".yellow()),
+                false => {
+                    for (note, todo) in todos.iter() {
+                        println!(
+                            "- {} {}",
+                            format!("[{}]", note.selector(&config.data_dir)).yellow(),
+                            todo.label()
+                        );
+                    }
+                }
+            }
+        }
+        args::actions::ActionArgs::Tasks {
+            action: Some(action),
+            ..
+        } => match action {
+            TasksArgs::Check { task } => run_set_task_done(&config, &task, true)?,
+            TasksArgs::Uncheck { task } => run_set_task_done(&config, &task, false)?,
+        },
+        args::actions::ActionArgs::Tasks {
+            folder,
+            open,
+            closed,
+            owner,
+            action: None,
+        } => {
+            let folder = Folder::from_pathbuf(&config.data_dir, folder)?;
+            let owner = owner.map(|owner| owner.trim_start_matches('@').to_lowercase());
+
+            let mut tasks = folder.list_tasks()?;
+            tasks.retain(|(_, task)| {
+                (!open || !task.done)
+                    && (!closed || task.done)
+                    && owner
+                        .as_ref()
+                        .is_none_or(|owner| task.owners.contains(owner))
+            });
+
+            if !output::is_text() {
+                let entries: Vec<_> = tasks
+                    .iter()
+                    .map(|(note, task)| {
+                        json!({
+                            "note": output::relative_path(&note.get_path(), &config.data_dir),
+                            "selector": format!("{}:{}", note.selector(&config.data_dir), task.line),
+                            "line": task.line,
+                            "text": task.text,
+                            "done": task.done,
+                            "due": task.due.map(|due| due.to_string()),
+                            "owners": task.owners,
+                        })
+                    })
+                    .collect();
+                output::emit_list(&entries)?;
+                return Ok(());
+            }
+
+            match tasks.is_empty() {
+                true => println!("{}", "No tasks found!".yellow()),
+                false => {
+                    for (note, task) in tasks.iter() {
+                        println!(
+                            "- {} {}",
+                            format!("[{}:{}]", note.selector(&config.data_dir), task.line).yellow(),
+                            task.label()
+                        );
+                    }
+                }
+            }
+        }
+        args::actions::ActionArgs::Daily { offset, date } => {
+            run_journal(&config, Period::Daily, offset, date)?
+        }
+        args::actions::ActionArgs::Weekly { offset, date } => {
+            run_journal(&config, Period::Weekly, offset, date)?
+        }
+        args::actions::ActionArgs::Monthly { offset, date } => {
+            run_journal(&config, Period::Monthly, offset, date)?
+        }
+        args::actions::ActionArgs::History { note, limit } => {
+            let note = Note::from_selector(&config.data_dir, &note)?;
+            let mut revisions = note.history(&config)?;
+            if let Some(limit) = limit {
+                revisions.truncate(limit);
+            }
+
+            if !output::is_text() {
+                let entries: Vec<_> = revisions
+                    .iter()
+                    .map(|revision| {
+                        json!({
+                            "hash": revision.hash,
+                            "path": revision.path,
+                            "date": revision.date,
+                            "author": revision.author,
+                            "message": revision.message,
+                            "added": revision.added,
+                            "removed": revision.removed,
+                        })
+                    })
+                    .collect();
+                output::emit_list(&entries)?;
+                return Ok(());
+            }
+
+            match revisions.is_empty() {
+                true => println!("{}", "No revisions found!".yellow()),
+                false => {
+                    for revision in revisions.iter() {
+                        println!("{}", revision.label());
+                    }
+                }
+            }
+        }
+        args::actions::ActionArgs::Show { note, rev } => {
+            let note = Note::from_selector(&config.data_dir, &note)?;
+            let passphrase = match note.is_encrypted() {
+                true => Some(prompt_passphrase(&format!(
+                    "Passphrase for '{}': ",
+                    note.name.blue()
+                ))?),
+                false => None,
+            };
+
+            let content = match (&rev, &passphrase) {
+                (Some(rev), _) => note.show_revision(&config, rev, passphrase.as_ref())?,
+                (None, Some(passphrase)) => note.decrypt_content(passphrase)?,
+                (None, None) => note.get_content()?,
+            };
+
+            match output::is_text() {
+                true => print!("{}", content),
+                false => output::emit(&json!({
+                    "note": output::relative_path(&note.get_path(), &config.data_dir),
+                    "rev": rev,
+                    "content": content,
+                }))?,
+            }
+        }
+        args::actions::ActionArgs::Diff { note, revisions } => {
+            let note = Note::from_selector(&config.data_dir, &note)?;
+            let diff = note.diff(
+                &config,
+                revisions.first().map(|rev| rev.as_str()),
+                revisions.get(1).map(|rev| rev.as_str()),
+            )?;
+
+            match output::is_text() {
+                true => print!("{}", diff),
+                false => output::emit(&json!({
+                    "note": output::relative_path(&note.get_path(), &config.data_dir),
+                    "revisions": revisions,
+                    "diff": diff,
+                }))?,
+            }
+        }
+        args::actions::ActionArgs::Restore { note, rev } => {
+            let note = Note::from_selector(&config.data_dir, &note)?;
+            note.restore(&config, &rev)?;
+
+            output::emit_success(
+                "restore",
+                json!({
+                    "note": output::relative_path(&note.get_path(), &config.data_dir),
+                    "rev": rev,
+                }),
+            )?;
+        }
+        args::actions::ActionArgs::Trash { action } => {
+            let trash = Trash::new(&config.data_dir);
+
+            match action {
+                TrashArgs::Ls => {
+                    let items = trash.items()?;
+
+                    if !output::is_text() {
+                        let entries: Vec<_> = items
+                            .iter()
+                            .map(|item| {
+                                json!({
+                                    "id": item.id,
+                                    "path": item.info.path,
+                                    "type": item.info.kind,
+                                    "deleted": item.info.deleted,
+                                })
+                            })
+                            .collect();
+                        output::emit_list(&entries)?;
+                        return Ok(());
+                    }
+
+                    match items.is_empty() {
+                        true => println!("{}", "The trash is empty!".yellow()),
+                        false => {
+                            for item in items.iter() {
+                                let path = match item.info.kind {
+                                    TrashKind::Folder => format!("{}/", item.info.path),
+                                    TrashKind::Note => item.info.path.clone(),
+                                };
+                                let deleted = item
+                                    .deleted_at()
+                                    .map(|deleted| deleted.format("%Y-%m-%d %H:%M").to_string())
+                                    .unwrap_or(item.info.deleted.clone());
+
+                                println!(
+                                    "{} {} {}",
+                                    format!("[{}]", item.id).yellow(),
+                                    path.blue(),
+                                    format!("(removed {})", deleted).dimmed()
+                                );
+                            }
+                        }
+                    }
+                }
+                TrashArgs::Restore { item } => {
+                    let item = trash.restore(&config, &item)?;
+                    output::emit_success(
+                        "trash restore",
+                        json!({ "id": item.id, "path": item.info.path }),
+                    )?;
+                }
+                TrashArgs::Empty { older_than } => {
+                    let items = trash.empty(older_than)?;
+                    output::emit_success(
+                        "trash empty",
+                        json!({
+                            "removed": items.iter().map(|item| &item.id).collect::<Vec<_>>(),
+                        }),
+                    )?;
+                }
+            }
+        }
+        args::actions::ActionArgs::Config { .. }
+        | args::actions::ActionArgs::MergeDriver { .. } => {
+            // already handled above
+        }
+    }
+
+    Ok(())
+}
+
+fn run_set_todo_done(config: &Config, selector: &str, done: bool) -> Result<()> {
+    let note = Note::from_selector(&config.data_dir, selector)?;
+    let todo = note.set_todo_done(config, done)?;
+
+    output::emit_success(
+        if done { "do" } else { "undo" },
+        json!({
+            "note": output::relative_path(&note.get_path(), &config.data_dir),
+            "title": todo.title,
+            "done": todo.done,
+        }),
+    )?;
+
+    Ok(())
+}
+
+fn run_set_task_done(config: &Config, selector: &str, done: bool) -> Result<()> {
+    let (note, line) = parse_task_selector(selector)?;
+    let note = Note::from_selector(&config.data_dir, note)?;
+    let task = note.set_task_done(config, line, done)?;
+
+    output::emit_success(
+        if done { "tasks check" } else { "tasks uncheck" },
+        json!({
+            "note": output::relative_path(&note.get_path(), &config.data_dir),
+            "line": task.line,
+            "text": task.text,
+            "done": task.done,
+        }),
+    )?;
+
+    Ok(())
+}
+
+/// Renders the template for a new note: the one given with `--template`, or the default of the
+/// folder the note goes into. Returns the front matter of the template and the body after it.
+fn render_note_template(
+    config: &Config,
+    name: &str,
+    title: Option<&str>,
+    template: Option<String>,
+    variables: Vec<(String, String)>,
+) -> Result<(Option<FrontMatter>, String)> {
+    let path = Path::new(name);
+    let directory = path
+        .parent()
+        .and_then(|parent| parent.to_str())
+        .unwrap_or("");
+    let folder = Folder::from_pathbuf(&config.data_dir, directory)?;
+
+    let template = match template.or(folder.default_template()?) {
+        None => return Ok((None, String::new())),
+        Some(value) => Template::load(&config.data_dir, &value)?,
+    };
+
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(name);
+    let mut context = TemplateContext::new(title.unwrap_or(stem), &folder.name);
+    context.variables = variables.into_iter().collect();
+
+    Ok(split_rendered(&template.render(&context)?)?)
+}
+
+fn run_journal(
+    config: &Config,
+    period: Period,
+    offset: Option<JournalOffset>,
+    date: Option<NaiveDate>,
+) -> Result<()> {
+    let date = date.unwrap_or_else(|| Local::now().date_naive());
+    let date = period.shift(date, offset.unwrap_or_default())?;
+
+    let (note, created) = Note::journal(config, period, date)?;
+    note.open(config)?;
+
+    output::emit_success(
+        period.name(),
+        json!({
+            "note": output::relative_path(&note.get_path(), &config.data_dir),
+            "created": created,
+        }),
+    )?;
+
+    Ok(())
+}
+
+fn run_merge_driver(ancestor: &str, current: &str, other: &str, path: Option<&str>) -> Result<()> {
+    let merged = merge_notes(
+        &fs::read_to_string(ancestor)?,
+        &fs::read_to_string(current)?,
+        &fs::read_to_string(other)?,
+    );
+    fs::write(current, &merged.content)?;
+
+    debug!(
+        "Merged '{}' with {} conflicts",
+        path.unwrap_or(current),
+        merged.conflicts
+    );
+
+    // tells git that the note still has conflict markers
+    if merged.conflicts > 0 {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn run_config_action(action: &ConfigArgs) -> Result<()> {
+    let path = ConfigFile::path();
+
+    if !output::is_text() {
+        match action {
+            ConfigArgs::Get { key } => {
+                output::emit(&json!({ "key": key, "value": ConfigFile::get(&path, key)? }))?
+            }
+            ConfigArgs::Set { key, value } => {
+                ConfigFile::set(&path, key, value)?;
+                output::emit_success("config set", json!({ "key": key, "value": value }))?;
+            }
+            ConfigArgs::List => {
+                let values: serde_json::Map<String, serde_json::Value> = ConfigFile::list(&path)?
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), json!(value)))
+                    .collect();
+                output::emit(&values)?;
+            }
+            ConfigArgs::Path => output::emit(&json!({ "path": path }))?,
+        }
+
+        return Ok(());
+    }
+
+    match action {
+        ConfigArgs::Get { key } => match ConfigFile::get(&path, key)? {
+            Some(value) => println!("{}", value),
+            None => println!("'{}' {}", key.blue(), "is not set".yellow()),
+        },
+        ConfigArgs::Set { key, value } => {
+            ConfigFile::set(&path, key, value)?;
+            println!("Set '{}' to '{}'", key.blue(), value.green());
+        }
+        ConfigArgs::List => {
+            for (key, value) in ConfigFile::list(&path)? {
+                match value {
+                    Some(value) => println!("{} = {}", key.blue(), value),
+                    None => println!("{} = {}", key.blue(), "(unset)".dimmed()),
+                }
+            }
         }
+        ConfigArgs::Path => println!("{}", path.to_str().unwrap()),
     }
 
     Ok(())
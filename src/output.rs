@@ -0,0 +1,188 @@
+use std::{
+    cell::Cell,
+    io::{IsTerminal, Write},
+    path::{Component, Path},
+    str::FromStr,
+    sync::OnceLock,
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+/// How results are written to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputFormat {
+    /// Coloured text for humans
+    #[default]
+    Text,
+    /// A single JSON document
+    Json,
+    /// One JSON object per line
+    Ndjson,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            other => Err(format!(
+                "unknown output format '{}', expected text, json or ndjson",
+                other
+            )),
+        }
+    }
+}
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+/// Sets the output format for the rest of the program. Only the first call has an effect.
+pub fn set_format(format: OutputFormat) {
+    let _ = FORMAT.set(format);
+}
+
+pub fn format() -> OutputFormat {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+pub fn is_text() -> bool {
+    format() == OutputFormat::Text
+}
+
+/// Prints a message meant for humans. With a structured format it goes to stderr, so that stdout
+/// only contains JSON.
+pub fn message(text: &str) {
+    match is_text() {
+        true => println!("{}", text),
+        false => eprintln!("{}", text),
+    }
+}
+
+/// Like [`message`], but without a trailing newline. Used for progress like `Syncing... Done`.
+pub fn message_inline(text: &str) {
+    match is_text() {
+        true => {
+            print!("{}", text);
+            let _ = std::io::stdout().flush();
+        }
+        false => {
+            eprint!("{}", text);
+            let _ = std::io::stderr().flush();
+        }
+    }
+}
+
+/// A status at the end of the current line that keeps replacing itself, like the percentage in
+/// `Syncing with remote... 42%`. It's only drawn on a terminal and erased again when dropped.
+#[derive(Default)]
+pub struct ProgressLine {
+    width: Cell<usize>,
+}
+
+impl ProgressLine {
+    fn is_terminal() -> bool {
+        match is_text() {
+            true => std::io::stdout().is_terminal(),
+            false => std::io::stderr().is_terminal(),
+        }
+    }
+
+    pub fn update(&self, text: &str) {
+        if !Self::is_terminal() {
+            return;
+        }
+
+        let width = text.chars().count();
+        let padding = self.width.get().saturating_sub(width);
+
+        message_inline(&format!(
+            "{}{}{}{}",
+            "\u{8}".repeat(self.width.get()),
+            text,
+            " ".repeat(padding),
+            "\u{8}".repeat(padding)
+        ));
+
+        self.width.set(width);
+    }
+
+    pub fn clear(&self) {
+        self.update("");
+    }
+}
+
+impl Drop for ProgressLine {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Writes a single object. Does nothing in text mode.
+pub fn emit<T: Serialize>(value: &T) -> Result<(), serde_json::Error> {
+    match format() {
+        OutputFormat::Text => {}
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value)?),
+        OutputFormat::Ndjson => println!("{}", serde_json::to_string(value)?),
+    }
+
+    Ok(())
+}
+
+/// Writes a list of objects, as one array for JSON and one line per item for NDJSON.
+/// Does nothing in text mode.
+pub fn emit_list<T: Serialize>(items: &[T]) -> Result<(), serde_json::Error> {
+    match format() {
+        OutputFormat::Text => {}
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(items)?),
+        OutputFormat::Ndjson => {
+            for item in items {
+                println!("{}", serde_json::to_string(item)?);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes `{"status": "ok", "action": ..}` merged with `details` for commands that don't
+/// return any data.
+pub fn emit_success(action: &str, details: Value) -> Result<(), serde_json::Error> {
+    let mut object = json!({ "status": "ok", "action": action });
+
+    if let (Some(object), Value::Object(details)) = (object.as_object_mut(), details) {
+        object.extend(details);
+    }
+
+    emit(&object)
+}
+
+/// Writes `{"status": "error", "error": ..}` for a failed command.
+pub fn emit_error(error: &anyhow::Error) -> Result<(), serde_json::Error> {
+    emit(&json!({
+        "status": "error",
+        "error": error.to_string(),
+        "causes": error.chain().skip(1).map(|cause| cause.to_string()).collect::<Vec<_>>(),
+    }))
+}
+
+/// Returns `path` relative to `root` the way it is shown in structured output, e.g. `work/todo.md`.
+pub fn relative_path(path: &Path, root: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+
+    relative
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Formats a file time as RFC 3339 in the local timezone.
+pub fn timestamp(time: SystemTime) -> String {
+    DateTime::<Local>::from(time).to_rfc3339()
+}